@@ -20,6 +20,26 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| Fish::quote_vec(black_box(&alphanumeric_long)))
     });
 
+    c.bench_function("fish quote_cow a-z", |b| {
+        b.iter(|| Fish::quote_cow(black_box(alphanumeric_short)))
+    });
+
+    c.bench_function("fish quote_cow a-z long", |b| {
+        b.iter(|| Fish::quote_cow(black_box(&alphanumeric_long)))
+    });
+
+    // A long string that needs no quoting, like a deep path.
+    let inert_long = "/usr/local/share/doc/shell-quote-0.8.0/README.md".repeat(1000);
+    c.bench_function("fish escape inert long", |b| {
+        b.iter(|| Fish::quote_vec(black_box(&inert_long)))
+    });
+
+    // The same, but with a single character that needs quoting at the end.
+    let mostly_inert_long = format!("{inert_long}~");
+    c.bench_function("fish escape mostly-inert long", |b| {
+        b.iter(|| Fish::quote_vec(black_box(&mostly_inert_long)))
+    });
+
     let bytes_short = (1..=255u8).map(char::from).collect::<String>();
     c.bench_function("fish escape bytes", |b| {
         b.iter(|| Fish::quote_vec(black_box(&bytes_short)))
@@ -1,78 +1,104 @@
-#![cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
-
 //! Scanner for ASCII control codes, shell metacharacters, printable characters,
 //! and extended codes, i.e. classify each byte in a stream according to where
-//! it appears in extended ASCII.
-//!
-//! # On widening the inert set
-//!
-//! [`Char::PrintableInert`] means "safe to emit bare, wherever this byte lands
-//! in a word, in every shell we support". Moving a byte into it makes output
-//! terser, which is nice, but the cost of being wrong is that we emit something
-//! the shell does not read as a single literal word – so the bar is high.
-//!
-//! `:`, `@`, and `+` clear it. Each was checked in leading, medial, and
-//! trailing position against `/bin/sh`, Bash 3.2 and 5.3, Dash, Z Shell 5.9,
-//! and fish 4.8.
-//!
-//! `%` and `=` were tried and rejected. Both are inert in most positions, and
-//! both could in principle be handled by tracking position within the word, but
-//! between them they attracted this list, and there is no reason to think it is
-//! complete:
-//!
-//! - `FOO=bar` is an assignment rather than a word in Bourne-like shells, and
-//!   in fish it is an outright error, "Unsupported use of '='".
-//! - `FOO+=bar` is an _append_ assignment in Bash and Z Shell. Note that `+` is
-//!   itself inert, so any position-tracking scheme has to know that a name may
-//!   be followed by an optional `+` before the `=`. This one was missed first
-//!   time round.
-//! - `=foo` is subject to `=` expansion in Z Shell, where `EQUALS` is on by
-//!   default. This matters here because `Zsh` is an alias for `Bash`.
-//! - With `MAGIC_EQUAL_SUBST` set, Z Shell expands after _any_ `=` in _any_
-//!   word: `--arg=~root` becomes `--arg=/var/root`. We survive that only
-//!   because `~`, `$`, `*`, `?`, and `[` are all still quoted; keep it that way.
-//! - `%1` in command position is a job specification. In Bash **no quoting
-//!   helps** – see the warning on [`crate::Bash`] – but in Z Shell quoting does
-//!   help, so making `%` inert would take something Z Shell can be protected
-//!   from and make it unfixable there too.
-//!
-//! For balance, these look dangerous and are not, all checked against Bash and
-//! Z Shell: `--arg=var`, `a.b=c`, `1=x`, `a+b=c`, and `FOO++=bar` cannot be read
-//! as assignments; and `-=`, `*=`, `/=`, `%=`, `<<=`, `>>=`, `&=`, `^=`, `|=`
-//! are _arithmetic_ operators, meaningful only inside `(( … ))`, `let`, and
-//! `$(( … ))`, never at the level of a word.
+//! it appears in extended ASCII, or each character according to where it
+//! appears in UTF-8.
 //!
-//! The lesson worth keeping: shell behaviour here is conditional on shell
-//! options (`EXTENDED_GLOB`, `MAGIC_EQUAL_SUBST`), on job control, and on
-//! whether the shell is interactive – none of which a plain non-interactive
-//! `sh -c` test exercises. Probing that way will under-report. It is how both
-//! `FOO+=bar` and `%1` were missed on the first attempt.
+//! The notes on which characters are inert, and why, are on [`Char`] so that
+//! they appear in the public documentation.
 
-use std::borrow::Borrow;
+use crate::Quotable;
 
-#[derive(PartialEq)]
-pub(crate) enum Char {
+/// The class of a byte or character, as far as quoting is concerned.
+///
+/// A [`Dialect`][`crate::Dialect`] decides how to render each class. Use
+/// [`Char::from_byte`] or [`Char::from_char`] to classify, e.g. to find out
+/// why a string needs quoting, without reproducing these tables.
+///
+/// # On widening the inert set
+///
+/// [`Char::PrintableInert`] means "safe to emit bare, wherever this byte lands
+/// in a word, in every shell we support". Moving a byte into it makes output
+/// terser, which is nice, but the cost of being wrong is that we emit something
+/// the shell does not read as a single literal word – so the bar is high.
+///
+/// `:`, `@`, and `+` clear it. Each was checked in leading, medial, and
+/// trailing position against `/bin/sh`, Bash 3.2 and 5.3, Dash, Z Shell 5.9,
+/// and fish 4.8.
+///
+/// `%` and `=` were tried and rejected. Both are inert in most positions, and
+/// both could in principle be handled by tracking position within the word, but
+/// between them they attracted this list, and there is no reason to think it is
+/// complete:
+///
+/// - `FOO=bar` is an assignment rather than a word in Bourne-like shells, and
+///   in fish it is an outright error, "Unsupported use of '='".
+/// - `FOO+=bar` is an _append_ assignment in Bash and Z Shell. Note that `+` is
+///   itself inert, so any position-tracking scheme has to know that a name may
+///   be followed by an optional `+` before the `=`. This one was missed first
+///   time round.
+/// - `=foo` is subject to `=` expansion in Z Shell, where `EQUALS` is on by
+///   default. [`Zsh`][`crate::Zsh`] relies on `=` not being inert.
+/// - With `MAGIC_EQUAL_SUBST` set, Z Shell expands after _any_ `=` in _any_
+///   word: `--arg=~root` becomes `--arg=/var/root`. We survive that only
+///   because `~`, `$`, `*`, `?`, and `[` are all still quoted; keep it that way.
+/// - `%1` in command position is a job specification. In Bash **no quoting
+///   helps** – see the warning on [`crate::Bash`] – but in Z Shell quoting does
+///   help, so making `%` inert would take something Z Shell can be protected
+///   from and make it unfixable there too.
+///
+/// For balance, these look dangerous and are not, all checked against Bash and
+/// Z Shell: `--arg=var`, `a.b=c`, `1=x`, `a+b=c`, and `FOO++=bar` cannot be read
+/// as assignments; and `-=`, `*=`, `/=`, `%=`, `<<=`, `>>=`, `&=`, `^=`, `|=`
+/// are _arithmetic_ operators, meaningful only inside `(( … ))`, `let`, and
+/// `$(( … ))`, never at the level of a word.
+///
+/// The lesson worth keeping: shell behaviour here is conditional on shell
+/// options (`EXTENDED_GLOB`, `MAGIC_EQUAL_SUBST`), on job control, and on
+/// whether the shell is interactive – none of which a plain non-interactive
+/// `sh -c` test exercises. Probing that way will under-report. It is how both
+/// `FOO+=bar` and `%1` were missed on the first attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Char {
+    /// `BEL`, 0x07, often escaped as `\a`.
     Bell,
+    /// `BS`, 0x08, often escaped as `\b`.
     Backspace,
+    /// `ESC`, 0x1B, often escaped as `\e`.
     Escape,
+    /// `FF`, 0x0C, often escaped as `\f`.
     FormFeed,
+    /// `LF`, 0x0A, often escaped as `\n`.
     NewLine,
+    /// `CR`, 0x0D, often escaped as `\r`.
     CarriageReturn,
+    /// `TAB`, 0x09, often escaped as `\t`.
     HorizontalTab,
+    /// `VT`, 0x0B, often escaped as `\v`.
     VerticalTab,
+    /// Any other ASCII control character, except `DEL`.
     Control(u8),
+    /// `\`.
     Backslash,
+    /// `'`.
     SingleQuote,
+    /// `"`.
     DoubleQuote,
+    /// `DEL`, 0x7F.
     Delete,
+    /// Printable ASCII that is safe to emit bare, wherever it lands in a word,
+    /// in every shell this crate supports.
     PrintableInert(u8),
+    /// Printable ASCII that may be significant to a shell.
     Printable(u8),
+    /// A byte of 0x80 or above, when quoting bytes.
     Extended(u8),
+    /// A character of U+0080 or above, when quoting text.
+    Utf8(char),
 }
 
 impl Char {
-    pub fn from<T: Borrow<u8>>(ch: T) -> Self {
-        let ch = *ch.borrow();
+    /// Classify a byte.
+    pub fn from_byte(ch: u8) -> Self {
         use Char::*;
         match ch {
             // ASCII control characters that frequently have dedicated backslash
@@ -112,8 +138,8 @@ impl Char {
             b'~' | b'!' | b'$' | b'*' | b'#' | b'^' => Printable(ch),
 
             // These two look inert and are not; they are quoted deliberately,
-            // and the module documentation above says at length why, so that
-            // nobody has to rediscover it.
+            // and the documentation on `Char` above says at length why, so
+            // that nobody has to rediscover it.
             b'%' | b'=' => Printable(ch),
 
             // ASCII extended characters, or high bytes.
@@ -121,16 +147,25 @@ impl Char {
         }
     }
 
-    #[inline]
-    pub fn is_inert(&self) -> bool {
+    /// Classify a character.
+    pub fn from_char(ch: char) -> Self {
+        match u8::try_from(ch) {
+            Ok(ascii) if ascii.is_ascii() => Self::from_byte(ascii),
+            _ => Char::Utf8(ch),
+        }
+    }
+
+    /// Whether this is [`Char::PrintableInert`], i.e. safe to emit bare.
+    pub fn is_inert(self) -> bool {
         matches!(self, Char::PrintableInert(_))
     }
 
-    #[inline]
+    /// The bytes of the character as they appeared in the input, i.e. the
+    /// inverse of [`Char::from_byte`] and [`Char::from_char`].
     #[cfg(feature = "sh")]
-    pub fn code(&self) -> u8 {
+    pub(crate) fn encode(self, buf: &mut [u8; 4]) -> &[u8] {
         use Char::*;
-        match *self {
+        let byte = match self {
             Bell => BEL,
             Backspace => BS,
             Escape => ESC,
@@ -139,16 +174,125 @@ impl Char {
             CarriageReturn => CR,
             HorizontalTab => TAB,
             VerticalTab => VT,
-            Control(ch) => ch,
             Backslash => b'\\',
             SingleQuote => b'\'',
             DoubleQuote => b'"',
             Delete => DEL,
-            PrintableInert(ch) => ch,
-            Printable(ch) => ch,
-            Extended(ch) => ch,
+            Control(ch) | PrintableInert(ch) | Printable(ch) | Extended(ch) => ch,
+            Utf8(ch) => return ch.encode_utf8(buf).as_bytes(),
+        };
+        buf[0] = byte;
+        &buf[..1]
+    }
+}
+
+impl From<u8> for Char {
+    fn from(ch: u8) -> Self {
+        Char::from_byte(ch)
+    }
+}
+
+impl From<char> for Char {
+    fn from(ch: char) -> Self {
+        Char::from_char(ch)
+    }
+}
+
+/// Find the index of the first byte in a string of bytes that is not
+/// [`Char::PrintableInert`]. Everything before it is written out as it is
+/// when quoting for any of the shells that this crate supports. There is none
+/// if the string is entirely inert, or empty – though an empty string is still
+/// quoted, as `''`.
+///
+/// Not every such character leads to quoting: [`Sh`][`crate::Sh`] writes
+/// bytes of 0x80 and above as they are, for example. Use `needs_quoting`, e.g.
+/// [`Bash::needs_quoting`][`crate::Bash::needs_quoting`], to find out if a
+/// string would be changed by quoting.
+///
+/// For text, the index is always at a character boundary, since every inert
+/// character is ASCII.
+///
+/// # Examples
+///
+/// ```
+/// # use shell_quote::first_unsafe_index;
+/// assert_eq!(first_unsafe_index("foo/bar-1.2"), None);
+/// assert_eq!(first_unsafe_index("foo bar"), Some(3));
+/// assert_eq!(first_unsafe_index("FOO=bar"), Some(3));
+/// assert_eq!(first_unsafe_index("café"), Some(3));
+/// ```
+pub fn first_unsafe_index<'a, S: Into<Quotable<'a>>>(s: S) -> Option<usize> {
+    first_non_inert(s.into().as_bytes())
+}
+
+/// Find the first byte in `sin` that is not [`Char::PrintableInert`].
+///
+/// Most strings that are quoted need no quoting at all, so this is the hot
+/// path. Rather than classify each byte with [`Char::from_byte`], this checks eight
+/// bytes at a time with some SWAR arithmetic – see [`inert_mask`] – then
+/// checks any remaining bytes with a lookup table.
+#[inline]
+pub(crate) fn first_non_inert(sin: &[u8]) -> Option<usize> {
+    let mut chunks = sin.chunks_exact(8);
+    for (index, chunk) in chunks.by_ref().enumerate() {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let not_inert = !inert_mask(word) & MSB;
+        if not_inert != 0 {
+            // Little-endian, so the lowest set bit is in the earliest byte.
+            return Some(index * 8 + (not_inert.trailing_zeros() / 8) as usize);
         }
     }
+    let rest = chunks.remainder();
+    rest.iter()
+        .position(|&ch| !INERT[ch as usize])
+        .map(|offset| sin.len() - rest.len() + offset)
+}
+
+/// Which bytes are [`Char::PrintableInert`]. This must agree with
+/// [`Char::from_byte`], and a test checks that it does.
+static INERT: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut ch = 0;
+    while ch < table.len() {
+        table[ch] = matches!(
+            ch as u8,
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b',' | b'.' | b'/' | b'_' | b'-' | b':' | b'@' | b'+'
+        );
+        ch += 1;
+    }
+    table
+};
+
+const LSB: u64 = u64::from_ne_bytes([0x01; 8]);
+const MSB: u64 = u64::from_ne_bytes([0x80; 8]);
+
+/// Set the high bit of each byte in `word` that is [`Char::PrintableInert`],
+/// and clear every other bit.
+///
+/// The inert bytes fall into four ranges: `+` to `:` (0x2B to 0x3A, which
+/// takes in `,`, `-`, `.`, `/`, and the digits), `@` to `Z` (0x40 to 0x5A),
+/// `_` (0x5F), and `a` to `z` (0x61 to 0x7A). This must agree with
+/// [`Char::from_byte`], and a test checks that it does.
+#[inline]
+const fn inert_mask(word: u64) -> u64 {
+    between(word, 0x2A, 0x3B)
+        | between(word, 0x3F, 0x5B)
+        | between(word, 0x5E, 0x60)
+        | between(word, 0x60, 0x7B)
+}
+
+/// Set the high bit of each byte in `word` that is strictly between `m` and
+/// `n`, and clear every other bit. Bytes of 0x80 and above are never between.
+///
+/// This is `hasbetween` from Sean Eron Anderson's [Bit Twiddling Hacks][bth].
+/// It requires `m <= 127` and `n <= 128`, and then no byte carries into or
+/// borrows from its neighbours.
+///
+/// [bth]: https://graphics.stanford.edu/~seander/bithacks.html#HasBetweenInWord
+#[inline]
+const fn between(word: u64, m: u8, n: u8) -> u64 {
+    let low = word & (LSB * 0x7F);
+    (LSB * (127 + n as u64) - low) & !word & (low + LSB * (127 - m as u64)) & MSB
 }
 
 const BEL: u8 = 0x07; // -> \a
@@ -163,12 +307,94 @@ const DEL: u8 = 0x7F;
 
 #[cfg(test)]
 mod tests {
+    use super::{first_non_inert, inert_mask, Char, INERT, MSB};
+
     #[test]
     #[cfg(feature = "sh")]
     fn test_code() {
         for ch in u8::MIN..=u8::MAX {
-            let char = super::Char::from(ch);
-            assert_eq!(ch, char.code());
+            let char = Char::from(ch);
+            assert_eq!([ch], char.encode(&mut [0; 4]));
+        }
+    }
+
+    #[test]
+    fn test_inert_table_agrees_with_char() {
+        for ch in u8::MIN..=u8::MAX {
+            assert_eq!(
+                INERT[ch as usize],
+                matches!(Char::from_byte(ch), Char::PrintableInert(_)),
+                "{ch:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_char_agrees_with_from_byte() {
+        for ch in 0..=0x7Fu8 {
+            assert_eq!(Char::from_char(ch.into()), Char::from_byte(ch));
+        }
+        for ch in ['\u{80}', 'é', 'ÿ', '😀'] {
+            assert_eq!(Char::from_char(ch), Char::Utf8(ch));
+        }
+    }
+
+    #[test]
+    fn test_inert_mask_agrees_with_char() {
+        for ch in u8::MIN..=u8::MAX {
+            // Surround `ch` with bytes that are, and are not, inert.
+            for other in [b'a', b' ', 0x00, 0xFF] {
+                let mut bytes = [other; 8];
+                bytes[3] = ch;
+                let mask = inert_mask(u64::from_le_bytes(bytes));
+                let expected = bytes.map(|ch| {
+                    if matches!(Char::from_byte(ch), Char::PrintableInert(_)) {
+                        0x80
+                    } else {
+                        0
+                    }
+                });
+                assert_eq!(mask, u64::from_le_bytes(expected) & MSB, "{ch:#04x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_non_inert() {
+        assert_eq!(first_non_inert(b""), None);
+        for len in 1..=20 {
+            let inert = std::vec![b'a'; len];
+            assert_eq!(first_non_inert(&inert), None);
+            for index in 0..len {
+                for ch in u8::MIN..=u8::MAX {
+                    let mut bytes = inert.clone();
+                    bytes[index] = ch;
+                    let expected = bytes
+                        .iter()
+                        .position(|&ch| !matches!(Char::from_byte(ch), Char::PrintableInert(_)));
+                    assert_eq!(first_non_inert(&bytes), expected, "{bytes:?}");
+                }
+            }
+        }
+    }
+
+    /// Every alignment and length up to two words, against a plain scan.
+    #[test]
+    fn test_first_non_inert_agrees_with_scan() {
+        let scan = |sin: &[u8]| sin.iter().position(|&ch| !Char::from_byte(ch).is_inert());
+        for start in 0..8 {
+            for len in 0..=16 {
+                for index in (start..start + len).map(Some).chain([None]) {
+                    for ch in [b' ', b'=', b'~', 0x00, 0x7F, 0x80, 0xFF] {
+                        let mut buffer = [b'a'; 24];
+                        if let Some(index) = index {
+                            buffer[index] = ch;
+                        }
+                        let sin = &buffer[start..start + len];
+                        assert_eq!(first_non_inert(sin), scan(sin), "{sin:?}");
+                    }
+                }
+            }
         }
     }
 }
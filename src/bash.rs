@@ -1,12 +1,45 @@
 #![cfg(feature = "bash")]
 
-use crate::{Quotable, QuoteInto};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "alloc")]
+use crate::explain::{explain, Explanation};
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+#[cfg(feature = "alloc")]
+use crate::word::{self, Kind, Renderer, Segment, ShellWord};
+use crate::{
+    ascii::Char,
+    dialect::{
+        needs_quoting, quote_into_sink, quote_into_slice, quoted_len, Dialect, Escaped, Quoting,
+        Render,
+    },
+    sink::{BufferTooSmall, FmtSink},
+    style::{
+        sealed::{SingleQuotes, Stylable},
+        Style, Styled,
+    },
+    Quotable, Quoted,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{escape_into_sink, join_into_sink, quote_cow},
+    sink::infallible,
+};
+#[cfg(feature = "alloc")]
+use crate::{JoinInto, QuoteInto, UnquoteError};
 
 /// Quote byte strings for use with Bash, the GNU Bourne-Again Shell.
 ///
 /// # Compatibility
 ///
-/// Quoted/escaped strings produced by [`Bash`] work in both Bash and Z Shell.
+/// Quoted/escaped strings produced by [`Bash`] work in both Bash and Z Shell,
+/// though [`Zsh`][`crate::Zsh`] follows Z Shell's own rules.
 ///
 /// # ⚠️ Warning regarding `NUL`
 ///
@@ -111,39 +144,115 @@ pub struct Bash;
 
 // ----------------------------------------------------------------------------
 
-impl QuoteInto<Vec<u8>> for Bash {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut Vec<u8>) {
-        Self::quote_into_vec(s, out);
+impl Dialect for Bash {
+    const OPEN: &'static [u8] = b"$'";
+    const CLOSE: &'static [u8] = b"'";
+    const QUOTE_WHOLE_WORD: bool = true;
+
+    fn render(ch: Char) -> Render {
+        // The whole word goes inside `$'...'`, where Bash recognises escape
+        // sequences, so nothing ever needs to be outside of quotes.
+        use Char::*;
+        let escaped = match ch {
+            Bell => Escaped::new(b"\\a"),
+            Backspace => Escaped::new(b"\\b"),
+            Escape => Escaped::new(b"\\e"),
+            FormFeed => Escaped::new(b"\\f"),
+            NewLine => Escaped::new(b"\\n"),
+            CarriageReturn => Escaped::new(b"\\r"),
+            HorizontalTab => Escaped::new(b"\\t"),
+            VerticalTab => Escaped::new(b"\\v"),
+            Control(ch) | Extended(ch) => Escaped::new(&u8_to_hex_escape(ch)),
+            Backslash => Escaped::new(b"\\\\"),
+            SingleQuote => Escaped::new(b"\\'"),
+            Delete => Escaped::new(b"\\x7F"),
+            PrintableInert(_) => return Render::Literal(Quoting::Either),
+            DoubleQuote | Printable(_) | Utf8(_) => return Render::Literal(Quoting::Inside),
+        };
+        Render::Escape(Quoting::Inside, escaped)
     }
 }
 
+#[cfg(feature = "alloc")]
 impl QuoteInto<String> for Bash {
     fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut String) {
         Self::quote_into_vec(s, unsafe { out.as_mut_vec() })
     }
 }
 
-#[cfg(unix)]
-impl QuoteInto<std::ffi::OsString> for Bash {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut std::ffi::OsString) {
-        use std::os::unix::ffi::OsStringExt;
-        let s = Self::quote_vec(s);
-        let s = std::ffi::OsString::from_vec(s);
-        out.push(s);
+#[cfg(feature = "alloc")]
+impl JoinInto<String> for Bash {
+    fn join_into<'q, I>(args: I, out: &mut String)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        Self::join_into_vec(args, unsafe { out.as_mut_vec() })
     }
 }
 
-#[cfg(feature = "bstr")]
-impl QuoteInto<bstr::BString> for Bash {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut bstr::BString) {
-        let s = Self::quote_vec(s);
-        out.extend(s);
+#[cfg(feature = "alloc")]
+impl Renderer for Bash {
+    fn render_segment(segment: &Segment<'_>, out: &mut Vec<u8>) {
+        match *segment {
+            Segment::Bare(s) => out.extend_from_slice(s.as_bytes()),
+            Segment::SingleQuoted(s) => word::single_quoted(s.as_bytes(), out),
+            // In an interactive shell, `!` may still begin history expansion
+            // in double quotes, and cannot be escaped there, so each one is
+            // written between them, as `\!`.
+            Segment::DoubleQuoted(s) => {
+                let contents = s.as_bytes();
+                for (index, run) in contents.split(|&ch| ch == b'!').enumerate() {
+                    if index > 0 {
+                        out.extend_from_slice(b"\\!");
+                    }
+                    if !run.is_empty() || contents.is_empty() {
+                        word::double_quoted(run, b"$`\"\\", out);
+                    }
+                }
+            }
+            Segment::AnsiC(s) => infallible(escape_into_sink::<Self, _>(s, 0, out)),
+            Segment::Escape(byte @ b' '..=b'~') => out.extend_from_slice(&[b'\\', byte]),
+            Segment::Escape(byte) => {
+                infallible(quote_into_sink::<Self, _>(Quotable::Bytes(&[byte]), out))
+            }
+        }
     }
 }
 
+impl Stylable for Bash {
+    type Plain = SingleQuotes;
+}
+
 // ----------------------------------------------------------------------------
 
 impl Bash {
+    /// How [`Bash`] quotes: the whole word, in ANSI-C quotes.
+    pub const STYLE: Style = Style {
+        prefer_plain_single_quotes: false,
+        whole_word: true,
+    };
+
+    /// Quote in a different [`Style`], e.g. with plain single quotes where
+    /// possible, or quoting only the characters that need it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use shell_quote::{Bash, Style};
+    /// let bash = Bash::with_style(Style { prefer_plain_single_quotes: true, ..Bash::STYLE });
+    /// assert_eq!(bash.quote_vec("foo bar"), b"'foo bar'");
+    /// assert_eq!(bash.quote_vec("foo\tbar"), b"$'foo\\tbar'");
+    /// let bash = Bash::with_style(Style { whole_word: false, ..Bash::STYLE });
+    /// assert_eq!(bash.quote_vec("foo bar"), b"foo$' bar'");
+    /// # }
+    /// ```
+    ///
+    pub const fn with_style(style: Style) -> Styled<Self> {
+        Styled::new(style)
+    }
+
     /// Quote a string of bytes into a new `Vec<u8>`.
     ///
     /// This will return one of the following:
@@ -164,31 +273,11 @@ impl Bash {
     /// [ansi-c-quoting]:
     ///     https://www.gnu.org/software/bash/manual/html_node/ANSI_002dC-Quoting.html
     ///
+    #[cfg(feature = "alloc")]
     pub fn quote_vec<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
-        // Here, previously, in the `Escape` cases, an optimisation
-        // precalculated the required capacity of the output `Vec` to avoid
-        // reallocations later on, but benchmarks showed that it was slower. It
-        // _may_ have lowered maximum RAM required, but that was not measured.
-        match s.into() {
-            Quotable::Bytes(bytes) => match bytes::escape_prepare(bytes) {
-                bytes::Prepared::Empty => vec![b'\'', b'\''],
-                bytes::Prepared::Inert => bytes.into(),
-                bytes::Prepared::Escape(esc) => {
-                    let mut sout = Vec::new();
-                    bytes::escape_chars(esc, &mut sout);
-                    sout
-                }
-            },
-            Quotable::Text(text) => match text::escape_prepare(text) {
-                text::Prepared::Empty => vec![b'\'', b'\''],
-                text::Prepared::Inert => text.into(),
-                text::Prepared::Escape(esc) => {
-                    let mut sout = Vec::new();
-                    text::escape_chars(esc, &mut sout);
-                    sout
-                }
-            },
-        }
+        let mut sout = Vec::new();
+        Self::quote_into_vec(s, &mut sout);
+        sout
     }
 
     /// Quote a string of bytes into an existing `Vec<u8>`.
@@ -206,127 +295,715 @@ impl Bash {
     /// assert_eq!(buf, b"foobar $'foo bar'");
     /// ```
     ///
+    #[cfg(feature = "alloc")]
     pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(s: S, sout: &mut Vec<u8>) {
-        // Here, previously, in the `Escape` cases, an optimisation
-        // precalculated the required capacity of the output `Vec` to avoid
-        // reallocations later on, but benchmarks showed that it was slower. It
-        // _may_ have lowered maximum RAM required, but that was not measured.
-        match s.into() {
-            Quotable::Bytes(bytes) => match bytes::escape_prepare(bytes) {
-                bytes::Prepared::Empty => sout.extend(b"''"),
-                bytes::Prepared::Inert => sout.extend(bytes),
-                bytes::Prepared::Escape(esc) => bytes::escape_chars(esc, sout),
-            },
-            Quotable::Text(text) => match text::escape_prepare(text) {
-                text::Prepared::Empty => sout.extend(b"''"),
-                text::Prepared::Inert => sout.extend(text.as_bytes()),
-                text::Prepared::Escape(esc) => text::escape_chars(esc, sout),
-            },
+        infallible(quote_into_sink::<Self, _>(s.into(), sout))
+    }
+
+    /// Quote a string of bytes, borrowing it if no quoting is necessary.
+    ///
+    /// This returns the same as [`quote_vec`][`Self::quote_vec`], but
+    /// allocates only when something needed quoting/escaping. An empty string
+    /// is quoted as `''`, which is borrowed too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::quote_cow("foobar"), Cow::Borrowed(b"foobar"));
+    /// assert_eq!(Bash::quote_cow("foo bar"), Cow::<[u8]>::Owned(b"$'foo bar'".to_vec()));
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, [u8]> {
+        quote_cow::<Self>(s.into())
+    }
+
+    /// Quote a string of bytes as text, borrowing it if no quoting is
+    /// necessary.
+    ///
+    /// This is [`quote_cow`][`Self::quote_cow`] for when a `str` is wanted.
+    /// The output is always valid UTF-8: text is quoted as text, and when
+    /// quoting bytes, everything outside of ASCII is escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::quote_cow_str("foobar"), Cow::Borrowed("foobar"));
+    /// assert_eq!(Bash::quote_cow_str("foo bar"), Cow::<str>::Owned("$'foo bar'".into()));
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow_str<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, str> {
+        // SAFETY: See above for why the output is always valid UTF-8; the
+        // `QuoteInto<String>` impl relies on the same.
+        match Self::quote_cow(s) {
+            Cow::Borrowed(bytes) => Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) }),
+            Cow::Owned(bytes) => Cow::Owned(unsafe { String::from_utf8_unchecked(bytes) }),
         }
     }
+
+    /// Check whether a string of bytes needs quoting/escaping, i.e. whether
+    /// [`quote_vec`][`Self::quote_vec`] would return anything other than the
+    /// string as it is.
+    ///
+    /// This is cheap: it stops at the first byte that needs quoting, and it
+    /// writes nothing. Note that an empty string needs quoting, as `''`. Use
+    /// [`first_unsafe_index`][`crate::first_unsafe_index`] to find out where
+    /// quoting starts, and [`Char`][`crate::Char`] to find out why.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert!(!Bash::needs_quoting("foo/bar-1.2"));
+    /// assert!(Bash::needs_quoting("foo bar"));
+    /// assert!(Bash::needs_quoting(""));
+    /// ```
+    ///
+    pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(s: S) -> bool {
+        needs_quoting::<Self>(s.into())
+    }
+
+    /// Explain why a string of bytes needs quoting/escaping, character by
+    /// character.
+    ///
+    /// There is an [`Explanation`] for each character that is quoted or
+    /// escaped, in order, saying where it is, why it must be quoted, and how
+    /// it is written. It is empty if the string needs no quoting, and also if
+    /// the string is empty, though that is quoted as `''`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Bash, Reason};
+    /// let why = Bash::explain("FOO=a\x1Bb");
+    /// assert_eq!(why.len(), 2);
+    /// assert_eq!(why[0].reason(), Reason::Assignment);
+    /// assert_eq!(why[0].to_string(), "`=` at 3: may start an assignment");
+    /// assert_eq!(why[1].to_string(), "0x1B at 5: control character, rendered as `\\e`");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn explain<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<Explanation> {
+        explain::<Self>(s.into())
+    }
+
+    /// Plan how to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// Rendering the plan with [`Bash`] gives the same output as
+    /// [`quote_vec`][`Self::quote_vec`]; it can also be inspected, changed,
+    /// or rendered for another shell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Quotable, Segment, Bash};
+    /// let word = Bash::plan("foo bar");
+    /// assert_eq!(
+    ///     word.segments(),
+    ///     [Segment::AnsiC(Quotable::Text("foo bar"))]
+    /// );
+    /// assert_eq!(word.render::<Bash>(), b"$'foo bar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan::<Self>(s.into(), Segment::AnsiC)
+    }
+
+    /// Plan the shortest way to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// This considers escaping characters with a backslash, single quotes,
+    /// double quotes, and ANSI-C quotes, and picks whichever gives the fewest
+    /// bytes when rendered with [`Bash`]. It is slower than
+    /// [`plan`][`Self::plan`], and its choices may change between releases;
+    /// use it where size matters more than a stable output.
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan_shortest::<Self>(
+            s.into(),
+            &[
+                Kind::Bare,
+                Kind::SingleQuoted,
+                Kind::DoubleQuoted,
+                Kind::AnsiC,
+                Kind::Escape,
+            ],
+        )
+    }
+
+    /// Quote a string of bytes into a new `Vec<u8>`, as briefly as possible.
+    ///
+    /// This renders the plan from [`plan_shortest`][`Self::plan_shortest`].
+    /// It is never longer than [`quote_vec`][`Self::quote_vec`], and often
+    /// shorter, but it is not the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::quote_vec_shortest("foo bar"), b"foo\\ bar");
+    /// assert_eq!(Bash::quote_vec_shortest("it's"), b"it\\'s");
+    /// assert_eq!(Bash::quote_vec("it's"), b"$'it\\'s'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        Self::plan_shortest(s).render::<Self>()
+    }
+
+    /// Quote a string of bytes exactly as Bash's `printf '%q'` does.
+    ///
+    /// Bash escapes special characters with a backslash, like `foo\ bar`,
+    /// unless there is a character that is not printable, in which case it
+    /// uses ANSI-C quoting instead, like `$'foo\tbar'`. The output is the same
+    /// as that of Bash 5.x in the `C` locale, byte for byte, which is useful
+    /// for comparing with scripts written by Bash itself. In other locales,
+    /// Bash may write printable non-ASCII characters as they are.
+    ///
+    /// `NUL`, which Bash cannot hold in a string, is written as `\000`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::printf_q("foo bar"), b"foo\\ bar");
+    /// assert_eq!(Bash::printf_q("~/it's"), b"\\~/it\\'s");
+    /// assert_eq!(Bash::printf_q("foo\tbar"), b"$'foo\\tbar'");
+    /// assert_eq!(Bash::printf_q(""), b"''");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn printf_q<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        let mut sout = Vec::new();
+        builtin::printf_q(s.into().as_bytes(), &mut sout);
+        sout
+    }
+
+    /// Quote a string of bytes exactly as Bash's `${var@Q}` does.
+    ///
+    /// Bash puts the whole string in single quotes, like `'foo bar'`, even
+    /// when nothing needs quoting, unless there is a character that is not
+    /// printable, in which case it uses ANSI-C quoting instead, like
+    /// `$'foo\tbar'`. As with [`printf_q`][`Self::printf_q`], the output is
+    /// the same as that of Bash 5.x in the `C` locale, byte for byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::at_q("foo"), b"'foo'");
+    /// assert_eq!(Bash::at_q("it's"), b"'it'\\''s'");
+    /// assert_eq!(Bash::at_q("'"), b"\\'");
+    /// assert_eq!(Bash::at_q("foo\tbar"), b"$'foo\\tbar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn at_q<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        let mut sout = Vec::new();
+        builtin::at_q(s.into().as_bytes(), &mut sout);
+        sout
+    }
+
+    /// Calculate the exact length, in bytes, of the quoted form of a string
+    /// of bytes, i.e. the length of what [`quote_vec`][`Self::quote_vec`]
+    /// would return.
+    ///
+    /// This does the work of quoting without keeping the output, so it is
+    /// about as costly as quoting. Use it to size a buffer up front, e.g. for
+    /// [`quote_into_slice`][`Self::quote_into_slice`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::quoted_len("foobar"), 6);
+    /// assert_eq!(Bash::quoted_len("foo bar"), 10); // $'foo bar'
+    /// ```
+    ///
+    pub fn quoted_len<'a, S: Into<Quotable<'a>>>(s: S) -> usize {
+        quoted_len::<Self>(s.into())
+    }
+
+    /// Quote a string of bytes into a caller-provided slice, without
+    /// allocating.
+    ///
+    /// On success, returns the number of bytes written to the start of
+    /// `sout`; these are the same bytes that [`quote_vec`][`Self::quote_vec`]
+    /// would return. If `sout` is too small, returns [`BufferTooSmall`], from
+    /// which the length needed can be found; the contents of `sout` are then
+    /// unspecified. Use [`quoted_len`][`Self::quoted_len`] to find the length
+    /// needed beforehand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut buf = [0u8; 16];
+    /// let len = Bash::quote_into_slice("foo bar", &mut buf)?;
+    /// assert_eq!(&buf[..len], b"$'foo bar'");
+    /// let err = Bash::quote_into_slice("foo bar", &mut buf[..4]).unwrap_err();
+    /// assert_eq!(err.needed(), 10);
+    /// # Ok::<(), shell_quote::BufferTooSmall>(())
+    /// ```
+    ///
+    pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut [u8],
+    ) -> Result<usize, BufferTooSmall> {
+        quote_into_slice::<Self>(s.into(), sout)
+    }
+
+    /// Quote a string of bytes into a [`fmt::Write`], like a [`String`] or a
+    /// [`fmt::Formatter`].
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but without an intermediate buffer. It fails only if `sout`
+    /// does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut buf = String::from("cat ");
+    /// Bash::quote_into_fmt("foo bar", &mut buf).unwrap();
+    /// assert_eq!(buf, "cat $'foo bar'");
+    /// ```
+    ///
+    pub fn quote_into_fmt<'a, S, W>(s: S, sout: &mut W) -> fmt::Result
+    where
+        S: Into<Quotable<'a>>,
+        W: fmt::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut FmtSink(sout))
+    }
+
+    /// Quote a string of bytes lazily, for use with `format!`, `write!`,
+    /// logging, etc.
+    ///
+    /// The returned [`Quoted`] implements [`Display`][`fmt::Display`] and
+    /// [`Debug`][`fmt::Debug`], writing what
+    /// [`quote_into_vec`][`Self::quote_into_vec`] would straight into the
+    /// formatter. Nothing is allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let arg = "foo bar";
+    /// assert_eq!(format!("cat {}", Bash::display(arg)), "cat $'foo bar'");
+    /// assert_eq!(format!("{:?}", Bash::display(arg)), "$'foo bar'");
+    /// ```
+    ///
+    pub fn display<'a, S: Into<Quotable<'a>>>(s: S) -> Quoted<'a, Self> {
+        Quoted::new(s.into())
+    }
+
+    /// Quote many strings of bytes into a new `Vec<u8>`, separated by spaces,
+    /// i.e. as a command line.
+    ///
+    /// Each argument is quoted as by [`quote_vec`][`Self::quote_vec`], so an
+    /// empty argument becomes `''`. No arguments at all produces an empty
+    /// `Vec`.
+    ///
+    /// See [`join_into_vec`][`Self::join_into_vec`] for a variant that extends
+    /// an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// assert_eq!(Bash::join_vec(["ls", "foo bar", ""]), b"ls $'foo bar' ''");
+    /// assert_eq!(Bash::join_vec(Vec::<&str>::new()), b"");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        Self::join_into_vec(args, &mut sout);
+        sout
+    }
+
+    /// Quote many strings of bytes into an existing `Vec<u8>`, separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] for more details. Note that no space
+    /// is pushed _before_ the first argument.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut buf = b"exec ".to_vec();
+    /// Bash::join_into_vec(["ls", "foo bar", ""], &mut buf);
+    /// assert_eq!(buf, [&b"exec "[..], b"ls $'foo bar' ''"].concat());
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_into_vec<'a, I>(args: I, sout: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        infallible(join_into_sink(args, sout, quote_into_sink::<Self, _>))
+    }
+
+    /// Quote a string of bytes into an [`io::Write`], like a
+    /// [`File`][`std::fs::File`] or a pipe.
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but as it goes, without an intermediate buffer. I/O errors are
+    /// returned. Output is written in many small pieces, so consider wrapping
+    /// an unbuffered writer in an [`io::BufWriter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut script = Vec::new(); // Or a `File`, a pipe, etc.
+    /// Bash::quote_into_writer("foo bar", &mut script).unwrap();
+    /// assert_eq!(script, b"$'foo bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn quote_into_writer<'a, S, W>(s: S, sout: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut IoSink(sout))
+    }
+
+    /// Quote many strings of bytes into an [`io::Write`], separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] and
+    /// [`quote_into_writer`][`Self::quote_into_writer`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut script = b"exec ".to_vec();
+    /// Bash::join_into_writer(["ls", "foo bar"], &mut script).unwrap();
+    /// assert_eq!(script, b"exec ls $'foo bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn join_into_writer<'a, I, W>(args: I, sout: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(sout), quote_into_sink::<Self, _>)
+    }
+
+    /// Unquote a string of bytes quoted for Bash into a new `Vec<u8>`.
+    ///
+    /// This is the inverse of [`quote_vec`][`Self::quote_vec`], but it also
+    /// understands words written by humans: bare characters, backslash
+    /// escapes, single quotes, double quotes, and [ANSI-C quoted
+    /// strings][ansi-c-quoting] like `$'foo\tbar'`, concatenated in any
+    /// combination.
+    ///
+    /// Every escape sequence in the table in the documentation for [`Bash`] is
+    /// decoded, as are `\E` (another escape character), `\"`, and `\?`.
+    /// Unrecognised escapes are left as they are, backslash and all, which is
+    /// what Bash does. `\uHHHH` and `\UHHHHHHHH` are encoded as UTF-8, which
+    /// is what Bash does in a UTF-8 locale. Note that `\0` and `\x00` decode
+    /// to NUL, even though Bash itself can mishandle it; see the warning on
+    /// [`Bash`].
+    ///
+    /// The input must be a single word that stands for a literal string. An
+    /// [`UnquoteError`] is returned for unterminated quotes, a trailing
+    /// backslash, an unquoted metacharacter – including whitespace – or an
+    /// expansion, like `$HOME`, `` `cmd` ``, or `*.txt`, since those mean
+    /// something to the shell that cannot be represented here.
+    ///
+    /// See [`unquote_into_vec`][`Self::unquote_into_vec`] for a variant that
+    /// extends an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Bash, UnquoteErrorKind};
+    /// assert_eq!(Bash::unquote("$'foo bar'").unwrap(), b"foo bar");
+    /// assert_eq!(Bash::unquote(r#"a'b'"c"$'\x64\n'"#).unwrap(), b"abcd\n");
+    /// let err = Bash::unquote("foo$bar").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Expansion(b'$'));
+    /// assert_eq!(err.position(), 3);
+    /// ```
+    ///
+    /// [ansi-c-quoting]:
+    ///     https://www.gnu.org/software/bash/manual/html_node/ANSI_002dC-Quoting.html
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<u8>, UnquoteError> {
+        let mut sout = Vec::new();
+        Self::unquote_into_vec(s, &mut sout)?;
+        Ok(sout)
+    }
+
+    /// Unquote a string of bytes quoted for Bash into an existing `Vec<u8>`.
+    ///
+    /// See [`unquote`][`Self::unquote`] for more details. On error, `sout` is
+    /// left as it was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Bash;
+    /// let mut buf = Vec::with_capacity(128);
+    /// Bash::unquote_into_vec("$'foo bar'", &mut buf).unwrap();
+    /// assert_eq!(buf, b"foo bar");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote_into_vec<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        let bytes = match s.into() {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(s) => s.as_bytes(),
+        };
+        crate::unquote::unquote(bytes, sout, unquote::read_word)
+    }
+
+    /// Split a command line quoted for Bash into words, unquoting each one.
+    ///
+    /// This is the inverse of quoting each argument and joining them with
+    /// spaces, and it understands the same quoting and escaping as
+    /// [`unquote`][`Self::unquote`]. Words are separated by unquoted spaces and
+    /// tabs, and a `#` at the start of a word begins a comment that runs to
+    /// the end of the line.
+    ///
+    /// Rather than pass them through, an [`UnquoteError`] is returned for
+    /// anything that cannot be represented as a plain list of arguments:
+    /// control operators and redirections like `|`, `;`, `&&`, and `>`, which
+    /// are reported as [`UnquoteErrorKind::Operator`]; expansions like `$HOME`
+    /// and `*.txt`; and any further command after a newline.
+    ///
+    /// [`UnquoteErrorKind::Operator`]: crate::UnquoteErrorKind::Operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Bash, UnquoteErrorKind};
+    /// let words = Bash::split("rsync -av 'my dir'/ $'x\\ty' # sync").unwrap();
+    /// assert_eq!(words, vec![&b"rsync"[..], b"-av", b"my dir/", b"x\ty"]);
+    /// let err = Bash::split("ls | wc").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'|'));
+    /// assert_eq!(err.position(), 3);
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn split<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<Vec<u8>>, UnquoteError> {
+        let bytes = match s.into() {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(s) => s.as_bytes(),
+        };
+        crate::unquote::split(bytes, unquote::read_word)
+    }
 }
 
 // ----------------------------------------------------------------------------
 
-mod bytes {
-    use super::u8_to_hex_escape;
-    use crate::ascii::Char;
+#[cfg(feature = "alloc")]
+mod unquote {
+    use alloc::vec::Vec;
 
-    pub enum Prepared {
-        Empty,
-        Inert,
-        Escape(Vec<Char>),
-    }
+    use crate::unquote::{
+        backslashed, check_bare, digits, double_quoted, single_quoted, Cursor, UnquoteError,
+        UnquoteErrorKind,
+    };
 
-    pub fn escape_prepare(sin: &[u8]) -> Prepared {
-        let esc: Vec<_> = sin.iter().map(Char::from).collect();
-        // An optimisation: if the string is not empty and contains only "safe"
-        // characters we can avoid further work.
-        if esc.is_empty() {
-            Prepared::Empty
-        } else if esc.iter().all(Char::is_inert) {
-            Prepared::Inert
-        } else {
-            Prepared::Escape(esc)
+    pub fn read_word(cur: &mut Cursor, sout: &mut Vec<u8>) -> Result<(), UnquoteError> {
+        while let Some((pos, ch)) = cur.next_in_word() {
+            match ch {
+                b'\'' => single_quoted(cur, pos, sout)?,
+                b'"' => double_quoted(cur, pos, sout)?,
+                b'\\' => backslashed(cur, pos, sout)?,
+                b'$' if cur.next_if(|ch| ch == b'\'').is_some() => ansi_c_quoted(cur, pos, sout)?,
+                ch => {
+                    check_bare(cur, ch, pos)?;
+                    sout.push(ch);
+                }
+            }
         }
+        Ok(())
     }
 
-    pub fn escape_chars(esc: Vec<Char>, sout: &mut Vec<u8>) {
-        // Push a Bash-style $'...' quoted string into `sout`.
-        sout.extend(b"$'");
-        for mode in esc {
-            use Char::*;
-            match mode {
-                Bell => sout.extend(b"\\a"),
-                Backspace => sout.extend(b"\\b"),
-                Escape => sout.extend(b"\\e"),
-                FormFeed => sout.extend(b"\\f"),
-                NewLine => sout.extend(b"\\n"),
-                CarriageReturn => sout.extend(b"\\r"),
-                HorizontalTab => sout.extend(b"\\t"),
-                VerticalTab => sout.extend(b"\\v"),
-                Control(ch) => sout.extend(&u8_to_hex_escape(ch)),
-                Backslash => sout.extend(b"\\\\"),
-                SingleQuote => sout.extend(b"\\'"),
-                DoubleQuote => sout.extend(b"\""),
-                Delete => sout.extend(b"\\x7F"),
-                PrintableInert(ch) => sout.push(ch),
-                Printable(ch) => sout.push(ch),
-                Extended(ch) => sout.extend(&u8_to_hex_escape(ch)),
+    /// Unquote the remainder of a `$'...'` string, the `$` of which was at
+    /// `start`. See the table in the documentation for [`super::Bash`].
+    fn ansi_c_quoted(
+        cur: &mut Cursor,
+        start: usize,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        use UnquoteErrorKind::*;
+        loop {
+            let (pos, ch) = match cur.next() {
+                Some((_, b'\'')) => break Ok(()),
+                Some((_, b'\\')) => match cur.next() {
+                    Some(next) => next,
+                    None => break Err(UnquoteError::new(UnterminatedAnsiCQuote, start)),
+                },
+                Some((_, ch)) => {
+                    sout.push(ch);
+                    continue;
+                }
+                None => break Err(UnquoteError::new(UnterminatedAnsiCQuote, start)),
+            };
+            match ch {
+                b'a' => sout.push(0x07),
+                b'b' => sout.push(0x08),
+                b'e' | b'E' => sout.push(0x1B),
+                b'f' => sout.push(0x0C),
+                b'n' => sout.push(0x0A),
+                b'r' => sout.push(0x0D),
+                b't' => sout.push(0x09),
+                b'v' => sout.push(0x0B),
+                b'\\' | b'\'' | b'"' | b'?' => sout.push(ch),
+                // Bash keeps only the low eight bits, so `\777` is 0xFF.
+                b'0'..=b'7' => sout.push(digits(cur, 8, 2, (ch - b'0').into()).0 as u8),
+                b'x' => match digits(cur, 16, 2, 0) {
+                    (_, 0) => sout.extend(b"\\x"),
+                    (n, _) => sout.push(n as u8),
+                },
+                b'u' | b'U' => match digits(cur, 16, if ch == b'u' { 4 } else { 8 }, 0) {
+                    (_, 0) => sout.extend([b'\\', ch]),
+                    (n, _) => match char::from_u32(n) {
+                        Some(ch) => sout.extend(ch.encode_utf8(&mut [0u8; 4]).as_bytes()),
+                        None => break Err(UnquoteError::new(InvalidEscape, pos - 1)),
+                    },
+                },
+                b'c' => match cur.peek() {
+                    // The closing quote cannot be the control character.
+                    None | Some(b'\'') => sout.extend(b"\\c"),
+                    // Bash reads `\c\\` as control-backslash, as does `\c\`.
+                    Some(b'\\') => {
+                        cur.next();
+                        cur.next_if(|ch| ch == b'\\');
+                        sout.push(0x1C);
+                    }
+                    Some(ch) => {
+                        cur.next();
+                        sout.push(match ch {
+                            b'?' => 0x7F,
+                            ch => ch.to_ascii_uppercase() & 0x1F,
+                        });
+                    }
+                },
+                ch => sout.extend([b'\\', ch]),
             }
         }
-        sout.push(b'\'');
     }
 }
 
 // ----------------------------------------------------------------------------
 
-mod text {
-    use super::u8_to_hex_escape;
-    use crate::utf8::Char;
+/// Quoting as Bash's own builtins do it, after `lib/sh/shquote.c` and
+/// `lib/sh/strtrans.c` in Bash's source, as they behave in the `C` locale.
+#[cfg(feature = "alloc")]
+mod builtin {
+    use alloc::vec::Vec;
 
-    pub enum Prepared {
-        Empty,
-        Inert,
-        Escape(Vec<Char>),
+    /// As `printf '%q'`.
+    pub fn printf_q(sin: &[u8], sout: &mut Vec<u8>) {
+        if sin.is_empty() {
+            sout.extend(b"''");
+        } else if should_ansi_c_quote(sin) {
+            ansi_c_quote(sin, sout);
+        } else {
+            backslash_quote(sin, sout);
+        }
     }
 
-    pub fn escape_prepare(sin: &str) -> Prepared {
-        let esc: Vec<_> = sin.chars().map(Char::from).collect();
-        // An optimisation: if the string is not empty and contains only "safe"
-        // characters we can avoid further work.
-        if esc.is_empty() {
-            Prepared::Empty
-        } else if esc.iter().all(Char::is_inert) {
-            Prepared::Inert
+    /// As `${var@Q}`.
+    pub fn at_q(sin: &[u8], sout: &mut Vec<u8>) {
+        if sin.is_empty() {
+            sout.extend(b"''");
+        } else if should_ansi_c_quote(sin) {
+            ansi_c_quote(sin, sout);
         } else {
-            Prepared::Escape(esc)
+            single_quote(sin, sout);
         }
     }
 
-    pub fn escape_chars(esc: Vec<Char>, sout: &mut Vec<u8>) {
-        // Push a Bash-style $'...' quoted string into `sout`.
+    /// Bash's `ansic_shouldquote`: are any of the bytes not printable?
+    fn should_ansi_c_quote(sin: &[u8]) -> bool {
+        !sin.iter().all(|ch| matches!(ch, b' '..=b'~'))
+    }
+
+    /// Bash's `ansic_quote`.
+    fn ansi_c_quote(sin: &[u8], sout: &mut Vec<u8>) {
         sout.extend(b"$'");
-        let buf = &mut [0u8; 4];
-        for mode in esc {
-            use Char::*;
-            match mode {
-                Bell => sout.extend(b"\\a"),
-                Backspace => sout.extend(b"\\b"),
-                Escape => sout.extend(b"\\e"),
-                FormFeed => sout.extend(b"\\f"),
-                NewLine => sout.extend(b"\\n"),
-                CarriageReturn => sout.extend(b"\\r"),
-                HorizontalTab => sout.extend(b"\\t"),
-                VerticalTab => sout.extend(b"\\v"),
-                Control(ch) => sout.extend(&u8_to_hex_escape(ch)),
-                Backslash => sout.extend(b"\\\\"),
-                SingleQuote => sout.extend(b"\\'"),
-                DoubleQuote => sout.extend(b"\""),
-                Delete => sout.extend(b"\\x7F"),
-                PrintableInert(ch) => sout.push(ch),
-                Printable(ch) => sout.push(ch),
-                Utf8(ch) => sout.extend(ch.encode_utf8(buf).as_bytes()),
+        for &ch in sin {
+            match ch {
+                0x1B => sout.extend(b"\\E"),
+                0x07 => sout.extend(b"\\a"),
+                0x0B => sout.extend(b"\\v"),
+                0x08 => sout.extend(b"\\b"),
+                0x0C => sout.extend(b"\\f"),
+                b'\n' => sout.extend(b"\\n"),
+                b'\r' => sout.extend(b"\\r"),
+                b'\t' => sout.extend(b"\\t"),
+                b'\\' | b'\'' => sout.extend([b'\\', ch]),
+                b' '..=b'~' => sout.push(ch),
+                // Always three digits, so it cannot run on into the next.
+                ch => sout.extend([
+                    b'\\',
+                    b'0' + (ch >> 6),
+                    b'0' + (ch >> 3 & 7),
+                    b'0' + (ch & 7),
+                ]),
+            }
+        }
+        sout.push(b'\'');
+    }
+
+    /// Bash's `sh_backslash_quote`, with the flags that `printf` uses.
+    fn backslash_quote(sin: &[u8], sout: &mut Vec<u8>) {
+        for (index, &ch) in sin.iter().enumerate() {
+            let escape = match ch {
+                b'\t' | b'\n' | b' ' | b'!' | b'"' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*'
+                | b',' | b';' | b'<' | b'>' | b'?' | b'[' | b'\\' | b']' | b'^' | b'`' | b'{'
+                | b'|' | b'}' => true,
+                // A comment, but only at the start of the word.
+                b'#' => index == 0,
+                // Tilde expansion, at the start of a word or after `:` or `=`.
+                b'~' => index == 0 || matches!(sin[index - 1], b':' | b'='),
+                _ => false,
+            };
+            if escape {
+                sout.push(b'\\');
+            }
+            sout.push(ch);
+        }
+    }
+
+    /// Bash's `sh_single_quote`.
+    fn single_quote(sin: &[u8], sout: &mut Vec<u8>) {
+        if sin == b"'" {
+            sout.extend(b"\\'");
+            return;
+        }
+        sout.push(b'\'');
+        for &ch in sin {
+            sout.push(ch);
+            if ch == b'\'' {
+                sout.extend(b"\\''");
             }
         }
         sout.push(b'\'');
@@ -354,7 +1031,7 @@ fn u8_to_hex_escape(ch: u8) -> [u8; 4] {
 #[test]
 fn test_u8_to_hex_escape() {
     for ch in u8::MIN..=u8::MAX {
-        let expected = format!("\\x{ch:02X}");
+        let expected = std::format!("\\x{ch:02X}");
         let observed = u8_to_hex_escape(ch);
         let observed = std::str::from_utf8(&observed).unwrap();
         assert_eq!(observed, &expected);
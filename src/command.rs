@@ -0,0 +1,189 @@
+#![cfg(all(
+    unix,
+    feature = "std",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+
+//! Render a [`std::process::Command`] as a line of shell script.
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::process::Command;
+use std::vec::Vec;
+
+use crate::QuoteInto;
+
+/// Shells into which a [`Command`] can be rendered with [`CommandExt`].
+///
+/// This describes the few pieces of syntax, beyond quoting, that rendering a
+/// command needs.
+pub trait CommandSyntax: QuoteInto<Vec<u8>> {
+    /// Whether the shell understands `NAME=value command` as setting `NAME`
+    /// in the environment of `command`. When it does not, `env NAME=value
+    /// command` is rendered instead.
+    const PREFIX_ASSIGNMENTS: bool;
+}
+
+#[cfg(feature = "sh")]
+impl CommandSyntax for crate::Sh {
+    const PREFIX_ASSIGNMENTS: bool = true;
+}
+
+#[cfg(feature = "bash")]
+impl CommandSyntax for crate::Bash {
+    const PREFIX_ASSIGNMENTS: bool = true;
+}
+
+/// fish only gained `NAME=value command` in version 3.1, so `env` is used for
+/// the benefit of older versions.
+#[cfg(feature = "fish")]
+impl CommandSyntax for crate::Fish {
+    const PREFIX_ASSIGNMENTS: bool = false;
+}
+
+/// Extension trait for rendering a [`Command`] as a line of shell script, e.g.
+/// for logging in a form that can be copied and pasted into a terminal.
+///
+/// The rendered line includes, in order:
+///
+/// - `cd DIR &&` if [`Command::current_dir`] was set,
+/// - `env -i` if [`EnvClear::Yes`] is given to [`to_shell_with`],
+/// - `env -u NAME` for each variable removed with [`Command::env_remove`],
+/// - `NAME=value` for each variable set with [`Command::env`],
+/// - then the program and its arguments.
+///
+/// When `env` is needed for clearing or removing variables, or when the shell
+/// does not understand `NAME=value` prefixes – see [`CommandSyntax`] – or when
+/// a variable's name is not valid in an assignment, all the variables are set
+/// via `env` instead of with prefixes.
+///
+/// A program with `=` in its name or path is run with `sh -c 'exec "$@"'` when
+/// `env` is used, since `env` would otherwise take it as an assignment.
+///
+/// Note that `cd` changes the working directory of the shell into which the
+/// line is pasted, not only of the command.
+///
+/// The standard library does not offer a way to find out if
+/// [`Command::env_clear`] has been called, so it is not reflected in the line
+/// that [`to_shell`] renders. Use [`to_shell_with`] to say so instead.
+///
+/// [`to_shell`]: Self::to_shell
+/// [`to_shell_with`]: Self::to_shell_with
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "bash", feature = "fish", feature = "sh"))] {
+/// # use std::process::Command;
+/// # use shell_quote::{Bash, CommandExt, Fish, Sh};
+/// let mut command = Command::new("ls");
+/// command.arg("-l").arg("my dir").env("LC_ALL", "C").current_dir("/tmp");
+/// assert_eq!(command.to_shell::<Sh>(), b"cd /tmp && LC_ALL=C ls -l my' dir'");
+/// assert_eq!(command.to_shell::<Bash>(), b"cd /tmp && LC_ALL=C ls -l $'my dir'");
+/// assert_eq!(command.to_shell::<Fish>(), b"cd /tmp && env LC_ALL'=C' ls -l my' dir'");
+/// # }
+/// ```
+pub trait CommandExt {
+    /// Render as a line of shell script for the shell `Q`.
+    fn to_shell<Q: CommandSyntax>(&self) -> Vec<u8> {
+        self.to_shell_with::<Q>(EnvClear::No)
+    }
+
+    /// Render as a line of shell script for the shell `Q`, with `env -i` if
+    /// [`Command::env_clear`] was called, as `env_clear` says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "sh")] {
+    /// # use std::process::Command;
+    /// # use shell_quote::{CommandExt, EnvClear, Sh};
+    /// let mut command = Command::new("ls");
+    /// command.env_clear().env("LC_ALL", "C");
+    /// assert_eq!(command.to_shell_with::<Sh>(EnvClear::Yes), b"env -i LC_ALL'=C' ls");
+    /// # }
+    /// ```
+    fn to_shell_with<Q: CommandSyntax>(&self, env_clear: EnvClear) -> Vec<u8>;
+}
+
+/// Whether [`Command::env_clear`] was called, for
+/// [`CommandExt::to_shell_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvClear {
+    /// The command inherits the environment, with changes.
+    No,
+    /// The command starts with an empty environment.
+    Yes,
+}
+
+impl CommandExt for Command {
+    fn to_shell_with<Q: CommandSyntax>(&self, env_clear: EnvClear) -> Vec<u8> {
+        let mut sout = Vec::new();
+
+        if let Some(dir) = self.get_current_dir() {
+            sout.extend(b"cd ");
+            Q::quote_into(dir, &mut sout);
+            sout.extend(b" && ");
+        }
+
+        let clear = env_clear == EnvClear::Yes;
+        let (set, removed): (Vec<_>, Vec<_>) = self.get_envs().partition(|(_, v)| v.is_some());
+        let use_env = clear
+            || !removed.is_empty()
+            || !set.is_empty() && !Q::PREFIX_ASSIGNMENTS
+            || !set.iter().all(|(name, _)| is_name(name));
+
+        if use_env {
+            sout.extend(b"env ");
+            if clear {
+                sout.extend(b"-i ");
+            } else {
+                for (name, _) in removed {
+                    sout.extend(b"-u ");
+                    Q::quote_into(name, &mut sout);
+                    sout.push(b' ');
+                }
+            }
+        }
+        for (name, value) in set {
+            let value = value.unwrap_or_default();
+            if use_env {
+                let assignment = [name.as_bytes(), b"=", value.as_bytes()].concat();
+                Q::quote_into(&assignment, &mut sout);
+            } else {
+                sout.extend(name.as_bytes());
+                sout.push(b'=');
+                Q::quote_into(value, &mut sout);
+            }
+            sout.push(b' ');
+        }
+
+        // `env` takes any argument with `=` in it as an assignment, even after
+        // `--`, so such a program is run by `sh` instead.
+        if use_env && self.get_program().as_bytes().contains(&b'=') {
+            sout.extend(b"sh -c ");
+            Q::quote_into("exec \"$@\"", &mut sout);
+            sout.extend(b" sh ");
+        }
+        Q::quote_into(self.get_program(), &mut sout);
+        for arg in self.get_args() {
+            sout.push(b' ');
+            Q::quote_into(arg, &mut sout);
+        }
+
+        sout
+    }
+}
+
+/// Can `name` appear on the left of `=` in an assignment?
+fn is_name(name: &OsStr) -> bool {
+    match name.as_bytes() {
+        [first, rest @ ..] => {
+            (first.is_ascii_alphabetic() || *first == b'_')
+                && rest
+                    .iter()
+                    .all(|ch| ch.is_ascii_alphanumeric() || *ch == b'_')
+        }
+        [] => false,
+    }
+}
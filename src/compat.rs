@@ -0,0 +1,309 @@
+#![cfg(feature = "sh")]
+
+//! Quoting exactly as other languages' libraries do it, for `/bin/sh`.
+//!
+//! Each type here has the same quoting and joining functions as [`Sh`], but
+//! none of its others: the output is fixed by the library copied, so there
+//! are no styles or plans, and to read it back, use [`Sh::unquote`] or
+//! [`Sh::split`].
+//!
+//! [`Sh`]: crate::Sh
+//! [`Sh::unquote`]: crate::Sh::unquote
+//! [`Sh::split`]: crate::Sh::split
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+use crate::{
+    ascii::Char,
+    dialect::{
+        needs_quoting, quote_into_sink, quote_into_slice, quoted_len, Dialect, Escaped, Quoting,
+        Render,
+    },
+    sink::{BufferTooSmall, FmtSink},
+    Quotable, Quoted,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{join_into_sink, quote_cow},
+    sink::infallible,
+};
+
+/// The quoting and joining functions of [`Sh`][`crate::Sh`], for each of the
+/// types here. See there for examples.
+macro_rules! quoting_functions {
+    ($name:ident) => {
+        impl $name {
+            /// Quote a string of bytes into a new `Vec<u8>`.
+            #[cfg(feature = "alloc")]
+            pub fn quote_vec<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+                let mut sout = Vec::new();
+                Self::quote_into_vec(s, &mut sout);
+                sout
+            }
+
+            /// Quote a string of bytes into an existing `Vec<u8>`.
+            #[cfg(feature = "alloc")]
+            pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(s: S, sout: &mut Vec<u8>) {
+                infallible(quote_into_sink::<Self, _>(s.into(), sout))
+            }
+
+            /// Quote a string of bytes, borrowing it if no quoting is
+            /// necessary.
+            #[cfg(feature = "alloc")]
+            pub fn quote_cow<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, [u8]> {
+                quote_cow::<Self>(s.into())
+            }
+
+            /// Check whether a string of bytes needs quoting/escaping.
+            pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(s: S) -> bool {
+                needs_quoting::<Self>(s.into())
+            }
+
+            /// Calculate the exact length, in bytes, of the quoted form of a
+            /// string of bytes.
+            pub fn quoted_len<'a, S: Into<Quotable<'a>>>(s: S) -> usize {
+                quoted_len::<Self>(s.into())
+            }
+
+            /// Quote a string of bytes into a caller-provided slice, without
+            /// allocating.
+            pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+                s: S,
+                sout: &mut [u8],
+            ) -> Result<usize, BufferTooSmall> {
+                quote_into_slice::<Self>(s.into(), sout)
+            }
+
+            /// Quote a string into a [`fmt::Write`].
+            pub fn quote_into_fmt<W: fmt::Write + ?Sized>(s: &str, sout: &mut W) -> fmt::Result {
+                quote_into_sink::<Self, _>(s.into(), &mut FmtSink(sout))
+            }
+
+            /// Quote a string lazily, for use with `format!`, `write!`,
+            /// logging, etc.
+            pub fn display(s: &str) -> Quoted<'_, Self> {
+                Quoted::new(s.into())
+            }
+
+            /// Quote many strings of bytes into a new `Vec<u8>`, separated by
+            /// spaces, i.e. as a command line.
+            #[cfg(feature = "alloc")]
+            pub fn join_vec<'a, I>(args: I) -> Vec<u8>
+            where
+                I: IntoIterator,
+                I::Item: Into<Quotable<'a>>,
+            {
+                let mut sout = Vec::new();
+                Self::join_into_vec(args, &mut sout);
+                sout
+            }
+
+            /// Quote many strings of bytes into an existing `Vec<u8>`,
+            /// separated by spaces, i.e. as a command line.
+            #[cfg(feature = "alloc")]
+            pub fn join_into_vec<'a, I>(args: I, sout: &mut Vec<u8>)
+            where
+                I: IntoIterator,
+                I::Item: Into<Quotable<'a>>,
+            {
+                infallible(join_into_sink(args, sout, quote_into_sink::<Self, _>))
+            }
+
+            /// Quote a string of bytes into an [`io::Write`].
+            #[cfg(feature = "std")]
+            pub fn quote_into_writer<'a, S, W>(s: S, sout: &mut W) -> io::Result<()>
+            where
+                S: Into<Quotable<'a>>,
+                W: io::Write + ?Sized,
+            {
+                quote_into_sink::<Self, _>(s.into(), &mut IoSink(sout))
+            }
+
+            /// Quote many strings of bytes into an [`io::Write`], separated by
+            /// spaces, i.e. as a command line.
+            #[cfg(feature = "std")]
+            pub fn join_into_writer<'a, I, W>(args: I, sout: &mut W) -> io::Result<()>
+            where
+                I: IntoIterator,
+                I::Item: Into<Quotable<'a>>,
+                W: io::Write + ?Sized,
+            {
+                join_into_sink(args, &mut IoSink(sout), quote_into_sink::<Self, _>)
+            }
+        }
+    };
+}
+
+/// Quote byte strings exactly as Python's [`shlex.quote`][shlex-quote] does,
+/// and join them as [`shlex.join`][shlex-join] does.
+///
+/// A string with only letters, digits, and `_@%+=:,./-` in it is written as it
+/// is. Anything else is put in single quotes, whole, with each `'` written as
+/// `'"'"'`. The output is for `/bin/sh`, and any shell like it.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::{QuoteRefExt, Shlex};
+/// let quoted: Vec<u8> = "it's".quoted(Shlex);
+/// assert_eq!(quoted, br#"'it'"'"'s'"#);
+/// let quoted: Vec<u8> = "--name=a%b".quoted(Shlex);
+/// assert_eq!(quoted, b"--name=a%b");
+/// # }
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::Shlex;
+/// assert_eq!(Shlex::quote_vec("my files"), b"'my files'");
+/// assert_eq!(Shlex::join_vec(["ls", "my files", ""]), b"ls 'my files' ''");
+/// # }
+/// ```
+///
+/// [shlex-quote]: https://docs.python.org/3/library/shlex.html#shlex.quote
+/// [shlex-join]: https://docs.python.org/3/library/shlex.html#shlex.join
+#[derive(Debug, Clone, Copy)]
+pub struct Shlex;
+
+quoting_functions!(Shlex);
+
+impl Dialect for Shlex {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+    const QUOTE_WHOLE_WORD: bool = true;
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            Char::SingleQuote => Render::Escape(Quoting::Inside, Escaped::new(b"'\"'\"'")),
+            // Python's `[\w@%+=:,./-]`, where `\w` is ASCII only: this
+            // crate's inert characters, and `%` and `=` too.
+            Char::PrintableInert(_) | Char::Printable(b'%' | b'=') => {
+                Render::Literal(Quoting::Either)
+            }
+            _ => Render::Literal(Quoting::Inside),
+        }
+    }
+
+    fn first_non_inert(sin: &[u8]) -> Option<usize> {
+        sin.iter()
+            .position(|&ch| Self::render(Char::from_byte(ch)) != Render::Literal(Quoting::Either))
+    }
+}
+
+/// Quote byte strings exactly as jq's [`@sh`][jq-sh] format does for a
+/// string.
+///
+/// Every string is put in single quotes, whole, even when nothing in it needs
+/// quoting, with each `'` written as `'\''`. Nothing is inert. The output is
+/// for `/bin/sh`, and any shell like it.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::{Jq, QuoteRefExt};
+/// let quoted: Vec<u8> = "it's".quoted(Jq);
+/// assert_eq!(quoted, br"'it'\''s'");
+/// let quoted: Vec<u8> = "foo".quoted(Jq);
+/// assert_eq!(quoted, b"'foo'");
+/// # }
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::Jq;
+/// assert_eq!(Jq::quote_vec("foo"), b"'foo'");
+/// assert!(Jq::needs_quoting("foo"));
+/// # }
+/// ```
+///
+/// [jq-sh]: https://jqlang.org/manual/#format-strings-and-escaping
+#[derive(Debug, Clone, Copy)]
+pub struct Jq;
+
+quoting_functions!(Jq);
+
+impl Dialect for Jq {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+    const QUOTE_WHOLE_WORD: bool = true;
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            Char::SingleQuote => Render::Escape(Quoting::Inside, Escaped::new(b"'\\''")),
+            _ => Render::Literal(Quoting::Inside),
+        }
+    }
+
+    fn first_non_inert(_: &[u8]) -> Option<usize> {
+        // Nothing is inert.
+        Some(0)
+    }
+}
+
+/// Quote byte strings exactly as Ruby's [`Shellwords.escape`][shellwords]
+/// does, as of Ruby 3.
+///
+/// A string with only letters, digits, and `_-.,:+/@` in it is written as it
+/// is. Anything else, except a newline, is escaped with a backslash; a newline
+/// is written as `'\n'`, since a backslash before it would join the lines.
+/// There are no quotes otherwise. Its set of characters that are written as
+/// they are is the same as this crate's; see [`Char::PrintableInert`]. The
+/// output is for `/bin/sh`, and any shell like it.
+///
+/// Characters of U+0080 and above, when quoting text, are escaped whole, like
+/// `\é`. Bytes of 0x80 and above, when quoting bytes, are each escaped, as
+/// Ruby does for a binary string.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::{QuoteRefExt, Shellwords};
+/// let quoted: Vec<u8> = "it's here".quoted(Shellwords);
+/// assert_eq!(quoted, br"it\'s\ here");
+/// let quoted: Vec<u8> = "a\nb".quoted(Shellwords);
+/// assert_eq!(quoted, b"a'\n'b");
+/// # }
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::Shellwords;
+/// assert_eq!(Shellwords::quote_vec("it's here"), br"it\'s\ here");
+/// assert_eq!(Shellwords::quoted_len("it's here"), 11);
+/// # }
+/// ```
+///
+/// [shellwords]: https://docs.ruby-lang.org/en/master/Shellwords.html#method-c-escape
+#[derive(Debug, Clone, Copy)]
+pub struct Shellwords;
+
+quoting_functions!(Shellwords);
+
+impl Dialect for Shellwords {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            Char::PrintableInert(_) => Render::Literal(Quoting::Either),
+            Char::NewLine => Render::Escape(Quoting::Outside, Escaped::new(b"'\n'")),
+            ch => {
+                let mut buf = [0; 4];
+                let bytes = ch.encode(&mut buf);
+                let mut escaped = [b'\\'; 5];
+                escaped[1..=bytes.len()].copy_from_slice(bytes);
+                Render::Escape(Quoting::Outside, Escaped::new(&escaped[..=bytes.len()]))
+            }
+        }
+    }
+}
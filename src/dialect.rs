@@ -0,0 +1,495 @@
+#![cfg_attr(
+    not(any(feature = "bash", feature = "fish", feature = "sh")),
+    allow(dead_code)
+)]
+
+//! Quoting rules, and the machinery that applies them.
+//!
+//! [`Sh`][`crate::Sh`], [`Bash`][`crate::Bash`], and [`Fish`][`crate::Fish`]
+//! are each described by an implementation of [`Dialect`]. Everything else –
+//! finding what needs quoting, writing runs of characters straight from the
+//! input, opening and closing quotes – is done here, once, for all of them.
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::{
+    ascii::{self, Char},
+    sink::{infallible, BufferTooSmall, Counter, Sink, SliceSink},
+    Quotable,
+};
+#[cfg(feature = "std")]
+use crate::{sink::IoSink, QuoteIntoWriter};
+#[cfg(feature = "alloc")]
+use crate::{JoinInto, QuoteInto};
+
+/// The rules for quoting/escaping strings for a particular shell, or
+/// shell-like language.
+///
+/// A dialect says how to [`render`][`Self::render`] each class of character
+/// – as it is, or as an escape sequence – and whether that must be done inside
+/// or outside of quotes. The crate takes care of the rest, and anything that
+/// implements [`Dialect`] gets [`QuoteInto`][`crate::QuoteInto`] – and so
+/// [`Quote`][`crate::Quote`], [`QuoteExt`][`crate::QuoteExt`], and
+/// [`QuoteRefExt`][`crate::QuoteRefExt`] – for [`Vec<u8>`][`alloc::vec::Vec`],
+/// [`OsString`][`std::ffi::OsString`] on Unix, and [`bstr::BString`], as well
+/// as [`QuoteIntoWriter`][`crate::QuoteIntoWriter`].
+///
+/// # Examples
+///
+/// A dialect for a language in which `@` introduces a variable, and in which
+/// single quotes work as in `/bin/sh`:
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use shell_quote::{Char, Dialect, Escaped, QuoteRefExt, Quoting, Render};
+///
+/// struct Dsl;
+///
+/// impl Dialect for Dsl {
+///     const OPEN: &'static [u8] = b"'";
+///     const CLOSE: &'static [u8] = b"'";
+///
+///     fn render(ch: Char) -> Render {
+///         match ch {
+///             Char::PrintableInert(b'@') => Render::Literal(Quoting::Inside),
+///             Char::PrintableInert(_) | Char::Extended(_) | Char::Utf8(_) => {
+///                 Render::Literal(Quoting::Either)
+///             }
+///             Char::SingleQuote => Render::Escape(Quoting::Outside, Escaped::new(b"\\'")),
+///             _ => Render::Literal(Quoting::Inside),
+///         }
+///     }
+///
+///     fn first_non_inert(sin: &[u8]) -> Option<usize> {
+///         sin.iter().position(|&ch| !matches!(Char::from_byte(ch), Char::PrintableInert(_)) || ch == b'@')
+///     }
+/// }
+///
+/// let quoted: Vec<u8> = "user@host".quoted(Dsl);
+/// assert_eq!(quoted, b"user'@host'");
+/// # }
+/// ```
+pub trait Dialect {
+    /// The bytes that open a quoted segment, e.g. `'`, or `$'` for Bash.
+    const OPEN: &'static [u8];
+
+    /// The bytes that close a quoted segment, e.g. `'`.
+    const CLOSE: &'static [u8];
+
+    /// The quoted form of an empty string.
+    const EMPTY: &'static [u8] = b"''";
+
+    /// Whether, once a word needs any quoting at all, the whole word is
+    /// quoted, as Bash does with `$'...'`. Otherwise quotes are opened only
+    /// when a character needs them, and closed when one cannot be in them.
+    const QUOTE_WHOLE_WORD: bool = false;
+
+    /// How to write `ch`, and whether that must be inside or outside of
+    /// quotes.
+    fn render(ch: Char) -> Render;
+
+    /// Find the first byte in `sin` that needs quoting/escaping. If there are
+    /// none, the string is written out as it is, unquoted.
+    ///
+    /// The default looks for anything other than [`Char::PrintableInert`].
+    /// It is fast; a dialect that needs a different set should override this
+    /// and [`render`][`Self::render`] together, and keep them in agreement.
+    fn first_non_inert(sin: &[u8]) -> Option<usize> {
+        ascii::first_non_inert(sin)
+    }
+
+    /// The number of bytes at the start of `sin` that, inside quotes, can be
+    /// written as they are. Quotes are open when this is called.
+    ///
+    /// This is an optimisation: it lets a dialect skip over characters that
+    /// it would [`render`][`Self::render`] as literals inside quotes without
+    /// looking at each one. The default is to skip nothing. The count must
+    /// end on a character boundary when `sin` is text.
+    fn literal_run(sin: &[u8]) -> usize {
+        let _ = sin;
+        0
+    }
+}
+
+/// Where a character, or its escape sequence, must be written relative to a
+/// dialect's quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    /// Inside quotes, e.g. characters that are special to the shell.
+    Inside,
+    /// Outside of quotes, e.g. escape sequences that the shell does not
+    /// recognise within quotes.
+    Outside,
+    /// Either; it means the same inside and outside of quotes.
+    Either,
+}
+
+/// How a dialect writes a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Render {
+    /// As it is.
+    Literal(Quoting),
+    /// As an escape sequence.
+    Escape(Quoting, Escaped),
+}
+
+/// An escape sequence, of up to [`Escaped::CAPACITY`] bytes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Escaped {
+    bytes: [u8; Escaped::CAPACITY],
+    len: u8,
+}
+
+impl Escaped {
+    /// The longest escape sequence that can be held.
+    pub const CAPACITY: usize = 16;
+
+    /// # Panics
+    ///
+    /// If `bytes` is longer than [`Escaped::CAPACITY`].
+    pub const fn new(bytes: &[u8]) -> Self {
+        assert!(bytes.len() <= Self::CAPACITY, "escape sequence is too long");
+        let mut escaped = Self {
+            bytes: [0; Self::CAPACITY],
+            len: bytes.len() as u8,
+        };
+        let mut index = 0;
+        while index < bytes.len() {
+            escaped.bytes[index] = bytes[index];
+            index += 1;
+        }
+        escaped
+    }
+
+    /// The escape sequence.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for Escaped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Escaped")
+            .field(&format_args!("\"{}\"", self.as_bytes().escape_ascii()))
+            .finish()
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// What needs to be done to quote a string.
+pub(crate) enum Prepared {
+    /// The string is empty; quote it as [`Dialect::EMPTY`].
+    Empty,
+    /// The string needs no quoting at all.
+    Inert,
+    /// The string needs quoting/escaping, starting at this byte offset. All
+    /// before it is inert.
+    Escape(usize),
+}
+
+/// Scan `sin` – once – to find out what needs to be done to quote it.
+///
+/// Every inert character is ASCII, so this works just as well on the bytes of
+/// a `str`: the offset in [`Prepared::Escape`] is always on a character
+/// boundary.
+#[inline]
+pub(crate) fn prepare<D: Dialect + ?Sized>(sin: &[u8]) -> Prepared {
+    if sin.is_empty() {
+        Prepared::Empty
+    } else {
+        match D::first_non_inert(sin) {
+            Some(start) => Prepared::Escape(start),
+            None => Prepared::Inert,
+        }
+    }
+}
+
+/// Quote into any [`Sink`]. All the quoting methods are built on this.
+pub(crate) fn quote_into_sink<D, S>(s: Quotable, sout: &mut S) -> Result<(), S::Error>
+where
+    D: Dialect + ?Sized,
+    S: Sink,
+{
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => sout.put(D::EMPTY),
+        Prepared::Inert => sout.put(s.as_bytes()),
+        Prepared::Escape(start) => escape_into_sink::<D, S>(s, start, sout),
+    }
+}
+
+/// Whether quoting `s` would change it. Mostly this is the same as asking if
+/// it has anything other than inert characters in it, but some dialects, like
+/// [`Sh`][`crate::Sh`], write some of those as they are, unquoted.
+pub(crate) fn needs_quoting<D: Dialect + ?Sized>(s: Quotable) -> bool {
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => true,
+        Prepared::Inert => false,
+        Prepared::Escape(start) => {
+            chars(s, start).any(|(_, ch)| D::render(ch) != Render::Literal(Quoting::Either))
+        }
+    }
+}
+
+/// Quote `s` into `sout`, all of which before `start` is inert.
+pub(crate) fn escape_into_sink<D, S>(
+    s: Quotable,
+    start: usize,
+    sout: &mut S,
+) -> Result<(), S::Error>
+where
+    D: Dialect + ?Sized,
+    S: Sink,
+{
+    // Here, previously, an optimisation precalculated the required capacity of
+    // the output `Vec` to avoid reallocations later on, but benchmarks showed
+    // that it was slower. It _may_ have lowered maximum RAM required, but that
+    // was not measured. Callers who want it can use `quoted_len` to size their
+    // buffer instead.
+    match s {
+        Quotable::Bytes(bytes) => escape_chars::<D, S>(bytes, None, start, sout),
+        Quotable::Text(text) => escape_chars::<D, S>(text.as_bytes(), Some(text), start, sout),
+    }
+}
+
+/// Quote `sin`, all of which before `start` is known to be inert. When `sin`
+/// is the bytes of `text`, characters of U+0080 and above are rendered as
+/// [`Char::Utf8`], otherwise bytes of 0x80 and above are rendered as
+/// [`Char::Extended`].
+#[inline]
+fn escape_chars<D, S>(
+    sin: &[u8],
+    text: Option<&str>,
+    start: usize,
+    sout: &mut S,
+) -> Result<(), S::Error>
+where
+    D: Dialect + ?Sized,
+    S: Sink,
+{
+    // Characters that are written as they are – as opposed to escaped – are
+    // written out in runs, straight from `sin`, broken only where a quote is
+    // opened or closed, or an escape sequence is written. Those are always at
+    // character boundaries, so text is written out in whole UTF-8 characters.
+    let mut inside_quotes = D::QUOTE_WHOLE_WORD;
+    if inside_quotes {
+        sout.put(D::OPEN)?;
+    }
+    let mut run = 0;
+    let mut index = start;
+    while let Some(&byte) = sin.get(index) {
+        let (ch, len) = next_char(byte, text, index);
+        let (quoting, escaped) = match D::render(ch) {
+            Render::Literal(Quoting::Either) => {
+                index += len;
+                continue;
+            }
+            Render::Literal(quoting) => (quoting, None),
+            Render::Escape(quoting, escaped) => (quoting, Some(escaped)),
+        };
+        if let (true, Quoting::Outside) | (false, Quoting::Inside) = (inside_quotes, quoting) {
+            sout.put(&sin[run..index])?;
+            sout.put(if inside_quotes { D::CLOSE } else { D::OPEN })?;
+            inside_quotes = !inside_quotes;
+            run = index;
+        }
+        if let Some(escaped) = escaped {
+            sout.put(&sin[run..index])?;
+            sout.put(escaped.as_bytes())?;
+            index += len;
+            run = index;
+        } else {
+            index += len;
+            if inside_quotes {
+                index += D::literal_run(&sin[index..]);
+            }
+        }
+    }
+    sout.put(&sin[run..])?;
+    if inside_quotes {
+        sout.put(D::CLOSE)?;
+    }
+    Ok(())
+}
+
+/// Classify the character starting with `byte`, at `index`. When quoting
+/// text, characters of U+0080 and above are [`Char::Utf8`], otherwise bytes of
+/// 0x80 and above are [`Char::Extended`]. Returns the length of the character
+/// in bytes too.
+#[inline]
+fn next_char(byte: u8, text: Option<&str>, index: usize) -> (Char, usize) {
+    match text {
+        Some(text) if !byte.is_ascii() => {
+            // Not ASCII, so this is the start of a multi-byte character.
+            let ch = text[index..].chars().next().unwrap_or_default();
+            (Char::Utf8(ch), ch.len_utf8())
+        }
+        _ => (Char::from_byte(byte), 1),
+    }
+}
+
+/// The characters of `s` from byte offset `start`, classified as by
+/// [`next_char`], with their byte offsets.
+pub(crate) fn chars(s: Quotable<'_>, start: usize) -> impl Iterator<Item = (usize, Char)> + '_ {
+    let (sin, text) = match s {
+        Quotable::Bytes(bytes) => (bytes, None),
+        Quotable::Text(text) => (text.as_bytes(), Some(text)),
+    };
+    let mut index = start;
+    core::iter::from_fn(move || {
+        let &byte = sin.get(index)?;
+        let (ch, len) = next_char(byte, text, index);
+        index += len;
+        Some((index - len, ch))
+    })
+}
+
+/// Quote each of `args` into `sout` with `quote`, separated by spaces, i.e. as
+/// a command line. All the joining methods are built on this.
+#[cfg(feature = "alloc")]
+pub(crate) fn join_into_sink<'q, I, S, F>(
+    args: I,
+    sout: &mut S,
+    mut quote: F,
+) -> Result<(), S::Error>
+where
+    I: IntoIterator,
+    I::Item: Into<Quotable<'q>>,
+    S: Sink + ?Sized,
+    F: FnMut(Quotable<'q>, &mut S) -> Result<(), S::Error>,
+{
+    for (index, arg) in args.into_iter().enumerate() {
+        if index > 0 {
+            sout.put(b" ")?;
+        }
+        quote(arg.into(), sout)?;
+    }
+    Ok(())
+}
+
+/// The exact length, in bytes, of `s` once quoted.
+pub(crate) fn quoted_len<D: Dialect + ?Sized>(s: Quotable) -> usize {
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => D::EMPTY.len(),
+        Prepared::Inert => s.as_bytes().len(),
+        Prepared::Escape(start) => {
+            let mut counter = Counter(0);
+            infallible(escape_into_sink::<D, _>(s, start, &mut counter));
+            counter.0
+        }
+    }
+}
+
+/// Quote `s` into the start of `sout`, returning the number of bytes written.
+pub(crate) fn quote_into_slice<D: Dialect + ?Sized>(
+    s: Quotable,
+    sout: &mut [u8],
+) -> Result<usize, BufferTooSmall> {
+    let available = sout.len();
+    let mut sink = SliceSink::new(sout);
+    match quote_into_sink::<D, _>(s, &mut sink) {
+        Ok(()) => Ok(sink.len()),
+        Err(()) => Err(BufferTooSmall::new(quoted_len::<D>(s), available)),
+    }
+}
+
+/// Quote `s`, borrowing it – or [`Dialect::EMPTY`] – if it needs no quoting.
+#[cfg(feature = "alloc")]
+pub(crate) fn quote_cow<D: Dialect + ?Sized>(s: Quotable<'_>) -> Cow<'_, [u8]> {
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => Cow::Borrowed(D::EMPTY),
+        Prepared::Inert => Cow::Borrowed(s.as_bytes()),
+        Prepared::Escape(start) => {
+            let mut sout = Vec::new();
+            infallible(escape_into_sink::<D, _>(s, start, &mut sout));
+            Cow::Owned(sout)
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "alloc")]
+impl<D: Dialect> QuoteInto<Vec<u8>> for D {
+    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut Vec<u8>) {
+        infallible(quote_into_sink::<D, _>(s.into(), out))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: Dialect> JoinInto<Vec<u8>> for D {
+    fn join_into<'q, I>(args: I, out: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        infallible(join_into_sink(args, out, quote_into_sink::<D, _>))
+    }
+}
+
+#[cfg(all(unix, feature = "std"))]
+impl<D: Dialect> QuoteInto<std::ffi::OsString> for D {
+    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut std::ffi::OsString) {
+        use std::os::unix::ffi::OsStringExt;
+        let mut sout = Vec::new();
+        D::quote_into(s, &mut sout);
+        out.push(std::ffi::OsString::from_vec(sout));
+    }
+}
+
+#[cfg(all(unix, feature = "std"))]
+impl<D: Dialect> JoinInto<std::ffi::OsString> for D {
+    fn join_into<'q, I>(args: I, out: &mut std::ffi::OsString)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        use std::os::unix::ffi::OsStringExt;
+        let mut sout = Vec::new();
+        D::join_into(args, &mut sout);
+        out.push(std::ffi::OsString::from_vec(sout));
+    }
+}
+
+#[cfg(all(feature = "bstr", feature = "alloc"))]
+impl<D: Dialect> QuoteInto<bstr::BString> for D {
+    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut bstr::BString) {
+        D::quote_into(s, &mut **out)
+    }
+}
+
+#[cfg(all(feature = "bstr", feature = "alloc"))]
+impl<D: Dialect> JoinInto<bstr::BString> for D {
+    fn join_into<'q, I>(args: I, out: &mut bstr::BString)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        D::join_into(args, &mut **out)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Dialect> QuoteIntoWriter for D {
+    fn quote_into_writer<'q, S, W>(s: S, out: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'q>>,
+        W: io::Write + ?Sized,
+    {
+        quote_into_sink::<D, _>(s.into(), &mut IoSink(out))
+    }
+
+    fn join_into_writer<'q, I, W>(args: I, out: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(out), quote_into_sink::<D, _>)
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+
+//! Lazy quoting, for use with `format!`, `write!`, logging, etc.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::{
+    dialect::{quote_into_sink, Dialect},
+    sink::FmtSink,
+    Quotable,
+};
+
+/// A string of bytes that will be quoted for the shell `Q` when formatted.
+///
+/// This is returned by [`Sh::display`][`crate::Sh::display`],
+/// [`Bash::display`][`crate::Bash::display`], and
+/// [`Fish::display`][`crate::Fish::display`]. Quoting happens each time it is
+/// formatted, straight into the [`fmt::Formatter`], without allocating. Both
+/// the [`Display`][`fmt::Display`] and [`Debug`][`fmt::Debug`] forms are the
+/// quoted string, so it works the same with `{}` and `{:?}`.
+#[derive(Clone, Copy)]
+pub struct Quoted<'a, Q> {
+    quotable: Quotable<'a>,
+    marker: PhantomData<Q>,
+}
+
+impl<'a, Q> Quoted<'a, Q> {
+    pub(crate) fn new(quotable: Quotable<'a>) -> Self {
+        Self {
+            quotable,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<D: Dialect> fmt::Display for Quoted<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `Sh::display` accepts only text, and `Sh` leaves bytes outside of
+        // ASCII as they are, so this writes valid UTF-8. `Bash` and `Fish`
+        // escape them.
+        quote_into_sink::<D, _>(self.quotable, &mut FmtSink(f))
+    }
+}
+
+impl<Q> fmt::Debug for Quoted<'_, Q>
+where
+    Self: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,222 @@
+//! Explanations of why a string was quoted/escaped the way it was.
+
+use core::fmt;
+
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+use alloc::vec::Vec;
+
+use crate::{ascii::Char, Render};
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+use crate::{
+    dialect::{chars, prepare, Dialect, Prepared},
+    Quotable, Quoting,
+};
+
+/// Why a character must be quoted/escaped.
+///
+/// This is derived from a character's [`Char`] class – see [`Reason::of`] –
+/// so it gives the reason that applies in any of the shells this crate
+/// supports. Not every reason applies in every position, nor in every shell,
+/// but the quoting in this crate does not track position, and errs on the side
+/// of caution; the documentation on [`Char`] explains more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// A control character, like `TAB` or `ESC`, or `DEL`.
+    Control,
+    /// Whitespace, which separates words.
+    Whitespace,
+    /// A control operator or redirection, like `|`, `;`, `&`, or `>`.
+    Operator,
+    /// `$` or `` ` ``, which begin parameter expansion or command
+    /// substitution.
+    Expansion,
+    /// `*`, `?`, `[`, or `]`, which are used in pathname expansion.
+    Pattern,
+    /// `{` or `}`, which are used in brace expansion.
+    Brace,
+    /// `~`, which may begin tilde expansion.
+    Tilde,
+    /// `!`, which may begin history expansion.
+    History,
+    /// `#`, which may begin a comment.
+    Comment,
+    /// `^`, which is a pipe in some old Bourne shells, and negates a pattern
+    /// in Z Shell with `EXTENDED_GLOB`.
+    Caret,
+    /// `=`, which may make a word into an assignment.
+    Assignment,
+    /// `%`, which may make a word into a job specification.
+    JobSpec,
+    /// A single or double quote.
+    Quote,
+    /// A backslash, which escapes the character after it.
+    Backslash,
+    /// A byte of 0x80 or above, when quoting bytes.
+    Extended,
+    /// A character of U+0080 or above, when quoting text.
+    NonAscii,
+    /// A character that is otherwise inert, but is special to a particular
+    /// [`Dialect`][`crate::Dialect`].
+    DialectSpecific,
+}
+
+impl Reason {
+    /// The reason that a character of class `ch` must be quoted/escaped.
+    ///
+    /// For [`Char::PrintableInert`] this is [`Reason::DialectSpecific`];
+    /// none of the shells that this crate supports quote those.
+    pub fn of(ch: Char) -> Self {
+        use Char::*;
+        match ch {
+            Bell | Backspace | Escape | FormFeed | NewLine | CarriageReturn | HorizontalTab
+            | VerticalTab | Control(_) | Delete => Reason::Control,
+            Backslash => Reason::Backslash,
+            SingleQuote | DoubleQuote => Reason::Quote,
+            Printable(b' ') => Reason::Whitespace,
+            Printable(b'|' | b'&' | b';' | b'(' | b')' | b'<' | b'>') => Reason::Operator,
+            Printable(b'$' | b'`') => Reason::Expansion,
+            Printable(b'*' | b'?' | b'[' | b']') => Reason::Pattern,
+            Printable(b'{' | b'}') => Reason::Brace,
+            Printable(b'~') => Reason::Tilde,
+            Printable(b'!') => Reason::History,
+            Printable(b'#') => Reason::Comment,
+            Printable(b'^') => Reason::Caret,
+            Printable(b'=') => Reason::Assignment,
+            Printable(b'%') => Reason::JobSpec,
+            Printable(_) | PrintableInert(_) => Reason::DialectSpecific,
+            Extended(_) => Reason::Extended,
+            Utf8(_) => Reason::NonAscii,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Reason::*;
+        f.write_str(match self {
+            Control => "control character",
+            Whitespace => "whitespace separates words",
+            Operator => "control operator or redirection",
+            Expansion => "may begin an expansion or substitution",
+            Pattern => "may be used in pathname expansion",
+            Brace => "may be used in brace expansion",
+            Tilde => "may begin tilde expansion",
+            History => "may begin history expansion",
+            Comment => "may begin a comment",
+            Caret => "may be a pipe, or negate a pattern",
+            Assignment => "may start an assignment",
+            JobSpec => "may be a job specification",
+            Quote => "quote",
+            Backslash => "backslash escapes the next character",
+            Extended => "byte outside of ASCII",
+            NonAscii => "character outside of ASCII",
+            DialectSpecific => "special to this dialect",
+        })
+    }
+}
+
+/// One character that had to be quoted/escaped: where it is, what it is, why
+/// it had to be, and how it was written.
+///
+/// These are returned by `explain`, e.g. [`Bash::explain`][`crate::Bash::explain`].
+/// The [`Display`][`fmt::Display`] form is a one-line summary, like
+/// ``0x1B at 9: control character, rendered as `\e` ``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation {
+    position: usize,
+    ch: Char,
+    render: Render,
+}
+
+impl Explanation {
+    /// The byte offset of the character in the input.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The class of the character.
+    pub fn char(&self) -> Char {
+        self.ch
+    }
+
+    /// Why the character had to be quoted/escaped.
+    pub fn reason(&self) -> Reason {
+        Reason::of(self.ch)
+    }
+
+    /// How the character was written, and whether it was inside or outside
+    /// of quotes.
+    pub fn render(&self) -> Render {
+        self.render
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ch {
+            Char::Utf8(ch) => write!(f, "`{ch}` (U+{:04X})", ch as u32)?,
+            ch => match byte_of(ch) {
+                byte @ (b' ' | b'!'..=b'~') => write!(f, "`{}`", byte as char)?,
+                byte => write!(f, "{byte:#04X}")?,
+            },
+        }
+        write!(f, " at {}: {}", self.position, self.reason())?;
+        if let Render::Escape(_, escaped) = self.render {
+            match core::str::from_utf8(escaped.as_bytes()) {
+                Ok(escaped) => write!(f, ", rendered as `{escaped}`")?,
+                Err(_) => write!(f, ", rendered as `{}`", escaped.as_bytes().escape_ascii())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The byte that `ch` was classified from. Not meaningful for
+/// [`Char::Utf8`].
+fn byte_of(ch: Char) -> u8 {
+    use Char::*;
+    match ch {
+        Bell => 0x07,
+        Backspace => 0x08,
+        HorizontalTab => 0x09,
+        NewLine => 0x0A,
+        VerticalTab => 0x0B,
+        FormFeed => 0x0C,
+        CarriageReturn => 0x0D,
+        Escape => 0x1B,
+        Delete => 0x7F,
+        Backslash => b'\\',
+        SingleQuote => b'\'',
+        DoubleQuote => b'"',
+        Control(ch) | PrintableInert(ch) | Printable(ch) | Extended(ch) => ch,
+        Utf8(_) => 0,
+    }
+}
+
+/// Explain each character in `s` that `D` quotes/escapes.
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+pub(crate) fn explain<D: Dialect + ?Sized>(s: Quotable) -> Vec<Explanation> {
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty | Prepared::Inert => Vec::new(),
+        Prepared::Escape(start) => chars(s, start)
+            .filter_map(|(position, ch)| match D::render(ch) {
+                Render::Literal(Quoting::Either) => None,
+                render => Some(Explanation {
+                    position,
+                    ch,
+                    render,
+                }),
+            })
+            .collect(),
+    }
+}
@@ -1,6 +1,35 @@
 #![cfg(feature = "fish")]
 
-use crate::{Quotable, QuoteInto};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "alloc")]
+use crate::explain::{explain, Explanation};
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+#[cfg(feature = "alloc")]
+use crate::word::{self, Kind, Renderer, Segment, ShellWord};
+use crate::{
+    ascii::Char,
+    dialect::{
+        needs_quoting, quote_into_sink, quote_into_slice, quoted_len, Dialect, Escaped, Quoting,
+        Render,
+    },
+    sink::{BufferTooSmall, FmtSink},
+    style::{sealed::Stylable, Style, Styled},
+    Quotable, Quoted,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{join_into_sink, quote_cow},
+    sink::infallible,
+};
+#[cfg(feature = "alloc")]
+use crate::{JoinInto, QuoteInto, UnquoteError};
 
 /// Quote byte strings for use with fish.
 ///
@@ -41,37 +70,118 @@ use crate::{Quotable, QuoteInto};
 #[derive(Debug, Clone, Copy)]
 pub struct Fish;
 
-impl QuoteInto<Vec<u8>> for Fish {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut Vec<u8>) {
-        Self::quote_into_vec(s, out);
+impl Dialect for Fish {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+
+    fn render(ch: Char) -> Render {
+        // Escape sequences are not recognised within single quotes, so
+        // control characters, for example, must be written outside of them.
+        use Char::*;
+        use Quoting::*;
+        match ch {
+            Bell => Render::Escape(Outside, Escaped::new(b"\\a")),
+            Backspace => Render::Escape(Outside, Escaped::new(b"\\b")),
+            Escape => Render::Escape(Outside, Escaped::new(b"\\e")),
+            FormFeed => Render::Escape(Outside, Escaped::new(b"\\f")),
+            NewLine => Render::Escape(Outside, Escaped::new(b"\\n")),
+            CarriageReturn => Render::Escape(Outside, Escaped::new(b"\\r")),
+            HorizontalTab => Render::Escape(Outside, Escaped::new(b"\\t")),
+            VerticalTab => Render::Escape(Outside, Escaped::new(b"\\v")),
+            Control(ch) | Extended(ch) => {
+                Render::Escape(Outside, Escaped::new(&u8_to_hex_escape_uppercase_x(ch)))
+            }
+            Backslash => Render::Escape(Either, Escaped::new(b"\\\\")),
+            SingleQuote => Render::Escape(Either, Escaped::new(b"\\'")),
+            DoubleQuote => Render::Literal(Inside),
+            Delete => Render::Escape(Outside, Escaped::new(b"\\X7F")),
+            PrintableInert(_) => Render::Literal(Either),
+            Printable(_) | Utf8(_) => Render::Literal(Inside),
+        }
     }
 }
 
+#[cfg(feature = "alloc")]
 impl QuoteInto<String> for Fish {
     fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut String) {
         Self::quote_into_vec(s, unsafe { out.as_mut_vec() })
     }
 }
 
-#[cfg(unix)]
-impl QuoteInto<std::ffi::OsString> for Fish {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut std::ffi::OsString) {
-        use std::os::unix::ffi::OsStringExt;
-        let s = Self::quote_vec(s);
-        let s = std::ffi::OsString::from_vec(s);
-        out.push(s);
+#[cfg(feature = "alloc")]
+impl JoinInto<String> for Fish {
+    fn join_into<'q, I>(args: I, out: &mut String)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        Self::join_into_vec(args, unsafe { out.as_mut_vec() })
     }
 }
 
-#[cfg(feature = "bstr")]
-impl QuoteInto<bstr::BString> for Fish {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut bstr::BString) {
-        let s = Self::quote_vec(s);
-        out.extend(s);
+#[cfg(feature = "alloc")]
+impl Renderer for Fish {
+    fn render_segment(segment: &Segment<'_>, out: &mut Vec<u8>) {
+        match *segment {
+            Segment::Bare(s) => out.extend_from_slice(s.as_bytes()),
+            Segment::SingleQuoted(s) => {
+                // Inside single quotes, fish understands `\'` and `\\`.
+                out.push(b'\'');
+                for &ch in s.as_bytes() {
+                    if let b'\'' | b'\\' = ch {
+                        out.push(b'\\');
+                    }
+                    out.push(ch);
+                }
+                out.push(b'\'');
+            }
+            Segment::DoubleQuoted(s) => word::double_quoted(s.as_bytes(), b"$\"\\", out),
+            // There's no ANSI-C quoting in fish, so quote it as anything else
+            // would be quoted.
+            Segment::AnsiC(s) => infallible(quote_into_sink::<Self, _>(s, out)),
+            // These are the characters that fish lets a backslash escape;
+            // others are quoted.
+            Segment::Escape(
+                byte @ (b' ' | b'$' | b'\\' | b'*' | b'?' | b'~' | b'#' | b'(' | b')' | b'{' | b'}'
+                | b'[' | b']' | b'<' | b'>' | b'^' | b'&' | b'|' | b';' | b'"' | b'\''),
+            ) => out.extend_from_slice(&[b'\\', byte]),
+            Segment::Escape(byte) => {
+                infallible(quote_into_sink::<Self, _>(Quotable::Bytes(&[byte]), out))
+            }
+        }
     }
 }
 
+impl Stylable for Fish {
+    type Plain = Self;
+}
+
 impl Fish {
+    /// How [`Fish`] quotes: only the characters that need it, in plain single
+    /// quotes.
+    pub const STYLE: Style = Style {
+        prefer_plain_single_quotes: true,
+        whole_word: false,
+    };
+
+    /// Quote in a different [`Style`], e.g. quoting the whole word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use shell_quote::{Fish, Style};
+    /// let fish = Fish::with_style(Style { whole_word: true, ..Fish::STYLE });
+    /// assert_eq!(fish.quote_vec("foo bar"), b"'foo bar'");
+    /// assert_eq!(fish.quote_vec("it's here"), b"'it\\'s here'");
+    /// assert_eq!(fish.quote_vec("one\ttwo"), b"'one'\\t'two'");
+    /// # }
+    /// ```
+    ///
+    pub const fn with_style(style: Style) -> Styled<Self> {
+        Styled::new(style)
+    }
+
     /// Quote a string of bytes into a new `Vec<u8>`.
     ///
     /// This will return one of the following:
@@ -88,27 +198,11 @@ impl Fish {
     /// assert_eq!(Fish::quote_vec("foobar"), b"foobar");
     /// assert_eq!(Fish::quote_vec("foo 'bar"), b"foo' \\'bar'");
     /// ```
+    #[cfg(feature = "alloc")]
     pub fn quote_vec<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
-        match s.into() {
-            Quotable::Bytes(bytes) => match bytes::escape_prepare(bytes) {
-                bytes::Prepared::Empty => vec![b'\'', b'\''],
-                bytes::Prepared::Inert => bytes.into(),
-                bytes::Prepared::Escape(esc) => {
-                    let mut sout = Vec::new();
-                    bytes::escape_chars(esc, &mut sout);
-                    sout
-                }
-            },
-            Quotable::Text(text) => match text::escape_prepare(text) {
-                text::Prepared::Empty => vec![b'\'', b'\''],
-                text::Prepared::Inert => text.into(),
-                text::Prepared::Escape(esc) => {
-                    let mut sout = Vec::new();
-                    text::escape_chars(esc, &mut sout);
-                    sout
-                }
-            },
-        }
+        let mut sout = Vec::new();
+        Self::quote_into_vec(s, &mut sout);
+        sout
     }
 
     /// Quote a string of bytes into an existing `Vec<u8>`.
@@ -126,172 +220,593 @@ impl Fish {
     /// assert_eq!(buf, b"foobar foo' \\'bar'");
     /// ```
     ///
+    #[cfg(feature = "alloc")]
     pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(s: S, sout: &mut Vec<u8>) {
-        match s.into() {
-            Quotable::Bytes(bytes) => match bytes::escape_prepare(bytes) {
-                bytes::Prepared::Empty => sout.extend(b"''"),
-                bytes::Prepared::Inert => sout.extend(bytes),
-                bytes::Prepared::Escape(esc) => bytes::escape_chars(esc, sout),
-            },
-            Quotable::Text(text) => match text::escape_prepare(text) {
-                text::Prepared::Empty => sout.extend(b"''"),
-                text::Prepared::Inert => sout.extend(text.as_bytes()),
-                text::Prepared::Escape(esc) => text::escape_chars(esc, sout),
-            },
+        infallible(quote_into_sink::<Self, _>(s.into(), sout))
+    }
+
+    /// Quote a string of bytes, borrowing it if no quoting is necessary.
+    ///
+    /// This returns the same as [`quote_vec`][`Self::quote_vec`], but
+    /// allocates only when something needed quoting/escaping. An empty string
+    /// is quoted as `''`, which is borrowed too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use shell_quote::Fish;
+    /// assert_eq!(Fish::quote_cow("foobar"), Cow::Borrowed(b"foobar"));
+    /// assert_eq!(Fish::quote_cow("foo bar"), Cow::<[u8]>::Owned(b"foo' bar'".to_vec()));
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, [u8]> {
+        quote_cow::<Self>(s.into())
+    }
+
+    /// Quote a string of bytes as text, borrowing it if no quoting is
+    /// necessary.
+    ///
+    /// This is [`quote_cow`][`Self::quote_cow`] for when a `str` is wanted.
+    /// The output is always valid UTF-8: text is quoted as text, and when
+    /// quoting bytes, everything outside of ASCII is escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use shell_quote::Fish;
+    /// assert_eq!(Fish::quote_cow_str("foobar"), Cow::Borrowed("foobar"));
+    /// assert_eq!(Fish::quote_cow_str("foo bar"), Cow::<str>::Owned("foo' bar'".into()));
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow_str<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, str> {
+        // SAFETY: See above for why the output is always valid UTF-8; the
+        // `QuoteInto<String>` impl relies on the same.
+        match Self::quote_cow(s) {
+            Cow::Borrowed(bytes) => Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) }),
+            Cow::Owned(bytes) => Cow::Owned(unsafe { String::from_utf8_unchecked(bytes) }),
         }
     }
-}
 
-// ----------------------------------------------------------------------------
+    /// Check whether a string of bytes needs quoting/escaping, i.e. whether
+    /// [`quote_vec`][`Self::quote_vec`] would return anything other than the
+    /// string as it is.
+    ///
+    /// This is cheap: it stops at the first byte that needs quoting, and it
+    /// writes nothing. Note that an empty string needs quoting, as `''`. Use
+    /// [`first_unsafe_index`][`crate::first_unsafe_index`] to find out where
+    /// quoting starts, and [`Char`][`crate::Char`] to find out why.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// assert!(!Fish::needs_quoting("foo/bar-1.2"));
+    /// assert!(Fish::needs_quoting("foo bar"));
+    /// assert!(Fish::needs_quoting(""));
+    /// ```
+    ///
+    pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(s: S) -> bool {
+        needs_quoting::<Self>(s.into())
+    }
 
-mod bytes {
-    use super::u8_to_hex_escape_uppercase_x;
-    use crate::ascii::Char;
+    /// Explain why a string of bytes needs quoting/escaping, character by
+    /// character.
+    ///
+    /// There is an [`Explanation`] for each character that is quoted or
+    /// escaped, in order, saying where it is, why it must be quoted, and how
+    /// it is written. It is empty if the string needs no quoting, and also if
+    /// the string is empty, though that is quoted as `''`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Fish, Reason};
+    /// let why = Fish::explain("FOO=a\x1Bb");
+    /// assert_eq!(why.len(), 2);
+    /// assert_eq!(why[0].reason(), Reason::Assignment);
+    /// assert_eq!(why[0].to_string(), "`=` at 3: may start an assignment");
+    /// assert_eq!(why[1].to_string(), "0x1B at 5: control character, rendered as `\\e`");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn explain<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<Explanation> {
+        explain::<Self>(s.into())
+    }
 
-    pub enum Prepared {
-        Empty,
-        Inert,
-        Escape(Vec<Char>),
+    /// Plan how to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// Rendering the plan with [`Fish`] gives the same output as
+    /// [`quote_vec`][`Self::quote_vec`]; it can also be inspected, changed,
+    /// or rendered for another shell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Quotable, Segment, Fish};
+    /// let word = Fish::plan("foo bar");
+    /// assert_eq!(
+    ///     word.segments(),
+    ///     [
+    ///         Segment::Bare(Quotable::Text("foo")),
+    ///         Segment::SingleQuoted(Quotable::Text(" bar")),
+    ///     ]
+    /// );
+    /// assert_eq!(word.render::<Fish>(), b"foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan::<Self>(s.into(), Segment::SingleQuoted)
     }
 
-    pub fn escape_prepare(sin: &[u8]) -> Prepared {
-        let esc: Vec<_> = sin.iter().map(Char::from).collect();
-        // An optimisation: if the string is not empty and contains only "safe"
-        // characters we can avoid further work.
-        if esc.is_empty() {
-            Prepared::Empty
-        } else if esc.iter().all(Char::is_inert) {
-            Prepared::Inert
-        } else {
-            Prepared::Escape(esc)
-        }
+    /// Plan the shortest way to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// This considers escaping characters with a backslash, single quotes,
+    /// and double quotes, and picks whichever gives the fewest bytes when
+    /// rendered with [`Fish`]. It is slower than [`plan`][`Self::plan`], and
+    /// its choices may change between releases; use it where size matters
+    /// more than a stable output.
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan_shortest::<Self>(
+            s.into(),
+            &[
+                Kind::Bare,
+                Kind::SingleQuoted,
+                Kind::DoubleQuoted,
+                Kind::Escape,
+            ],
+        )
     }
 
-    pub fn escape_chars(esc: Vec<Char>, sout: &mut Vec<u8>) {
-        #[derive(PartialEq)]
-        enum QuoteStyle {
-            Inside,
-            Outside,
-            Whatever,
-        }
-        use QuoteStyle::*;
-
-        let mut inside_quotes_now = false;
-        let mut push_literal = |style: QuoteStyle, literal: &[u8]| {
-            match (inside_quotes_now, style) {
-                (true, Outside) => {
-                    sout.push(b'\'');
-                    inside_quotes_now = false;
-                }
-                (false, Inside) => {
-                    sout.push(b'\'');
-                    inside_quotes_now = true;
-                }
-                _ => (),
-            }
-            sout.extend(literal);
+    /// Quote a string of bytes into a new `Vec<u8>`, as briefly as possible.
+    ///
+    /// This renders the plan from [`plan_shortest`][`Self::plan_shortest`].
+    /// It is never longer than [`quote_vec`][`Self::quote_vec`], and often
+    /// shorter, but it is not the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// assert_eq!(Fish::quote_vec_shortest("foo bar"), b"foo\\ bar");
+    /// assert_eq!(Fish::quote_vec_shortest("$HOME's"), b"\\$HOME\\'s");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        Self::plan_shortest(s).render::<Self>()
+    }
+
+    /// Calculate the exact length, in bytes, of the quoted form of a string
+    /// of bytes, i.e. the length of what [`quote_vec`][`Self::quote_vec`]
+    /// would return.
+    ///
+    /// This does the work of quoting without keeping the output, so it is
+    /// about as costly as quoting. Use it to size a buffer up front, e.g. for
+    /// [`quote_into_slice`][`Self::quote_into_slice`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// assert_eq!(Fish::quoted_len("foobar"), 6);
+    /// assert_eq!(Fish::quoted_len("foo bar"), 9); // foo' bar'
+    /// ```
+    ///
+    pub fn quoted_len<'a, S: Into<Quotable<'a>>>(s: S) -> usize {
+        quoted_len::<Self>(s.into())
+    }
+
+    /// Quote a string of bytes into a caller-provided slice, without
+    /// allocating.
+    ///
+    /// On success, returns the number of bytes written to the start of
+    /// `sout`; these are the same bytes that [`quote_vec`][`Self::quote_vec`]
+    /// would return. If `sout` is too small, returns [`BufferTooSmall`], from
+    /// which the length needed can be found; the contents of `sout` are then
+    /// unspecified. Use [`quoted_len`][`Self::quoted_len`] to find the length
+    /// needed beforehand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut buf = [0u8; 16];
+    /// let len = Fish::quote_into_slice("foo bar", &mut buf)?;
+    /// assert_eq!(&buf[..len], b"foo' bar'");
+    /// let err = Fish::quote_into_slice("foo bar", &mut buf[..4]).unwrap_err();
+    /// assert_eq!(err.needed(), 9);
+    /// # Ok::<(), shell_quote::BufferTooSmall>(())
+    /// ```
+    ///
+    pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut [u8],
+    ) -> Result<usize, BufferTooSmall> {
+        quote_into_slice::<Self>(s.into(), sout)
+    }
+
+    /// Quote a string of bytes into a [`fmt::Write`], like a [`String`] or a
+    /// [`fmt::Formatter`].
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but without an intermediate buffer. It fails only if `sout`
+    /// does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut buf = String::from("cat ");
+    /// Fish::quote_into_fmt("foo 'bar", &mut buf).unwrap();
+    /// assert_eq!(buf, "cat foo' \\'bar'");
+    /// ```
+    ///
+    pub fn quote_into_fmt<'a, S, W>(s: S, sout: &mut W) -> fmt::Result
+    where
+        S: Into<Quotable<'a>>,
+        W: fmt::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut FmtSink(sout))
+    }
+
+    /// Quote a string of bytes lazily, for use with `format!`, `write!`,
+    /// logging, etc.
+    ///
+    /// The returned [`Quoted`] implements [`Display`][`fmt::Display`] and
+    /// [`Debug`][`fmt::Debug`], writing what
+    /// [`quote_into_vec`][`Self::quote_into_vec`] would straight into the
+    /// formatter. Nothing is allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let arg = "foo 'bar";
+    /// assert_eq!(format!("cat {}", Fish::display(arg)), "cat foo' \\'bar'");
+    /// assert_eq!(format!("{:?}", Fish::display(arg)), "foo' \\'bar'");
+    /// ```
+    ///
+    pub fn display<'a, S: Into<Quotable<'a>>>(s: S) -> Quoted<'a, Self> {
+        Quoted::new(s.into())
+    }
+
+    /// Quote many strings of bytes into a new `Vec<u8>`, separated by spaces,
+    /// i.e. as a command line.
+    ///
+    /// Each argument is quoted as by [`quote_vec`][`Self::quote_vec`], so an
+    /// empty argument becomes `''`. No arguments at all produces an empty
+    /// `Vec`.
+    ///
+    /// See [`join_into_vec`][`Self::join_into_vec`] for a variant that extends
+    /// an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// assert_eq!(Fish::join_vec(["ls", "foo bar", ""]), b"ls foo' bar' ''");
+    /// assert_eq!(Fish::join_vec(Vec::<&str>::new()), b"");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        Self::join_into_vec(args, &mut sout);
+        sout
+    }
+
+    /// Quote many strings of bytes into an existing `Vec<u8>`, separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] for more details. Note that no space
+    /// is pushed _before_ the first argument.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut buf = b"exec ".to_vec();
+    /// Fish::join_into_vec(["ls", "foo bar", ""], &mut buf);
+    /// assert_eq!(buf, [&b"exec "[..], b"ls foo' bar' ''"].concat());
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_into_vec<'a, I>(args: I, sout: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        infallible(join_into_sink(args, sout, quote_into_sink::<Self, _>))
+    }
+
+    /// Quote a string of bytes into an [`io::Write`], like a
+    /// [`File`][`std::fs::File`] or a pipe.
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but as it goes, without an intermediate buffer. I/O errors are
+    /// returned. Output is written in many small pieces, so consider wrapping
+    /// an unbuffered writer in an [`io::BufWriter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut script = Vec::new(); // Or a `File`, a pipe, etc.
+    /// Fish::quote_into_writer("foo bar", &mut script).unwrap();
+    /// assert_eq!(script, b"foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn quote_into_writer<'a, S, W>(s: S, sout: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut IoSink(sout))
+    }
+
+    /// Quote many strings of bytes into an [`io::Write`], separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] and
+    /// [`quote_into_writer`][`Self::quote_into_writer`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut script = b"exec ".to_vec();
+    /// Fish::join_into_writer(["ls", "foo bar"], &mut script).unwrap();
+    /// assert_eq!(script, b"exec ls foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn join_into_writer<'a, I, W>(args: I, sout: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(sout), quote_into_sink::<Self, _>)
+    }
+
+    /// Unquote a string of bytes quoted for fish into a new `Vec<u8>`.
+    ///
+    /// This is the inverse of [`quote_vec`][`Self::quote_vec`], but it also
+    /// understands words written by humans, following fish's rules for
+    /// [quoting][] and [escaping characters][]:
+    ///
+    /// - In single quotes, only `\\` and `\'` are escapes.
+    /// - In double quotes, only `\"`, `\$`, `\\`, and backslash-newline.
+    /// - Outside of quotes, `\a`, `\b`, `\e`, `\f`, `\n`, `\r`, `\t`, `\v`,
+    ///   `\xHH`, `\XHH`, `\ooo`, `\uXXXX`, `\UXXXXXXXX`, and `\cX`, and a
+    ///   backslash before any other character makes it literal.
+    ///
+    /// `\xHH`, `\XHH`, and `\ooo` produce a single byte, and `\uXXXX` and
+    /// `\UXXXXXXXX` produce UTF-8.
+    ///
+    /// The input must be a single word that stands for a literal string. An
+    /// [`UnquoteError`] is returned for unterminated quotes, a trailing
+    /// backslash, a malformed escape sequence, an unquoted metacharacter –
+    /// including whitespace – or an expansion, like `$HOME` or `*.txt`, since
+    /// those mean something to the shell that cannot be represented here.
+    ///
+    /// See [`unquote_into_vec`][`Self::unquote_into_vec`] for a variant that
+    /// extends an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Fish, UnquoteErrorKind};
+    /// assert_eq!(Fish::unquote("foo' \\'bar'").unwrap(), b"foo 'bar");
+    /// assert_eq!(Fish::unquote(r"'foo'\t'bar'\X7F").unwrap(), b"foo\tbar\x7F");
+    /// let err = Fish::unquote("\"$HOME\"").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Expansion(b'$'));
+    /// assert_eq!(err.position(), 1);
+    /// ```
+    ///
+    /// [quoting]: https://fishshell.com/docs/current/language.html#quotes
+    /// [escaping characters]:
+    ///     https://fishshell.com/docs/current/language.html#escaping-characters
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<u8>, UnquoteError> {
+        let mut sout = Vec::new();
+        Self::unquote_into_vec(s, &mut sout)?;
+        Ok(sout)
+    }
+
+    /// Unquote a string of bytes quoted for fish into an existing `Vec<u8>`.
+    ///
+    /// See [`unquote`][`Self::unquote`] for more details. On error, `sout` is
+    /// left as it was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Fish;
+    /// let mut buf = Vec::with_capacity(128);
+    /// Fish::unquote_into_vec("foo' \\'bar'", &mut buf).unwrap();
+    /// assert_eq!(buf, b"foo 'bar");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote_into_vec<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        let bytes = match s.into() {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(s) => s.as_bytes(),
         };
-        for mode in esc {
-            use Char::*;
-            match mode {
-                Bell => push_literal(Outside, b"\\a"),
-                Backspace => push_literal(Outside, b"\\b"),
-                Escape => push_literal(Outside, b"\\e"),
-                FormFeed => push_literal(Outside, b"\\f"),
-                NewLine => push_literal(Outside, b"\\n"),
-                CarriageReturn => push_literal(Outside, b"\\r"),
-                HorizontalTab => push_literal(Outside, b"\\t"),
-                VerticalTab => push_literal(Outside, b"\\v"),
-                Control(ch) => push_literal(Outside, &u8_to_hex_escape_uppercase_x(ch)),
-                Backslash => push_literal(Whatever, b"\\\\"),
-                SingleQuote => push_literal(Whatever, b"\\'"),
-                DoubleQuote => push_literal(Inside, b"\""),
-                Delete => push_literal(Outside, b"\\X7F"),
-                PrintableInert(ch) => push_literal(Whatever, &ch.to_le_bytes()),
-                Printable(ch) => push_literal(Inside, &ch.to_le_bytes()),
-                Extended(ch) => push_literal(Outside, &u8_to_hex_escape_uppercase_x(ch)),
-            }
-        }
-        if inside_quotes_now {
-            sout.push(b'\'');
-        }
+        crate::unquote::unquote(bytes, sout, unquote::read_word)
+    }
+
+    /// Split a command line quoted for fish into words, unquoting each one.
+    ///
+    /// This is the inverse of quoting each argument and joining them with
+    /// spaces, and it understands the same quoting and escaping as
+    /// [`unquote`][`Self::unquote`]. Words are separated by unquoted spaces and
+    /// tabs, and a `#` at the start of a word begins a comment that runs to
+    /// the end of the line.
+    ///
+    /// Rather than pass them through, an [`UnquoteError`] is returned for
+    /// anything that cannot be represented as a plain list of arguments:
+    /// control operators and redirections like `|`, `;`, `&&`, and `>`, which
+    /// are reported as [`UnquoteErrorKind::Operator`]; expansions like `$HOME`
+    /// and `*.txt`; and any further command after a newline.
+    ///
+    /// [`UnquoteErrorKind::Operator`]: crate::UnquoteErrorKind::Operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Fish, UnquoteErrorKind};
+    /// let words = Fish::split("rsync -av 'my dir'/ x\\ty # sync").unwrap();
+    /// assert_eq!(words, vec![&b"rsync"[..], b"-av", b"my dir/", b"x\ty"]);
+    /// let err = Fish::split("ls | wc").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'|'));
+    /// assert_eq!(err.position(), 3);
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn split<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<Vec<u8>>, UnquoteError> {
+        let bytes = match s.into() {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(s) => s.as_bytes(),
+        };
+        crate::unquote::split(bytes, unquote::read_word)
     }
 }
 
 // ----------------------------------------------------------------------------
 
-mod text {
-    use super::u8_to_hex_escape_uppercase_x;
-    use crate::utf8::Char;
+#[cfg(feature = "alloc")]
+mod unquote {
+    use alloc::vec::Vec;
 
-    pub enum Prepared {
-        Empty,
-        Inert,
-        Escape(Vec<Char>),
-    }
+    use crate::unquote::{check_bare, digits, Cursor, UnquoteError, UnquoteErrorKind};
 
-    pub fn escape_prepare(sin: &str) -> Prepared {
-        let esc: Vec<_> = sin.chars().map(Char::from).collect();
-        // An optimisation: if the string is not empty and contains only "safe"
-        // characters we can avoid further work.
-        if esc.is_empty() {
-            Prepared::Empty
-        } else if esc.iter().all(Char::is_inert) {
-            Prepared::Inert
-        } else {
-            Prepared::Escape(esc)
+    pub fn read_word(cur: &mut Cursor, sout: &mut Vec<u8>) -> Result<(), UnquoteError> {
+        while let Some((pos, ch)) = cur.next_in_word() {
+            match ch {
+                b'\'' => single_quoted(cur, pos, sout)?,
+                b'"' => double_quoted(cur, pos, sout)?,
+                b'\\' => backslashed(cur, pos, sout)?,
+                ch => {
+                    check_bare(cur, ch, pos)?;
+                    sout.push(ch);
+                }
+            }
         }
+        Ok(())
     }
 
-    pub fn escape_chars(esc: Vec<Char>, sout: &mut Vec<u8>) {
-        #[derive(PartialEq)]
-        enum QuoteStyle {
-            Inside,
-            Outside,
-            Whatever,
-        }
-        use QuoteStyle::*;
-
-        let mut inside_quotes_now = false;
-        let mut push_literal = |style: QuoteStyle, literal: &[u8]| {
-            match (inside_quotes_now, style) {
-                (true, Outside) => {
-                    sout.push(b'\'');
-                    inside_quotes_now = false;
-                }
-                (false, Inside) => {
-                    sout.push(b'\'');
-                    inside_quotes_now = true;
+    /// Unquote the remainder of a single-quoted string, the opening quote of
+    /// which was at `start`. Only `\\` and `\'` are escapes.
+    fn single_quoted(
+        cur: &mut Cursor,
+        start: usize,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        loop {
+            match cur.next() {
+                Some((_, b'\'')) => break Ok(()),
+                Some((_, b'\\')) => match cur.next_if(|ch| ch == b'\\' || ch == b'\'') {
+                    Some(ch) => sout.push(ch),
+                    None => sout.push(b'\\'),
+                },
+                Some((_, ch)) => sout.push(ch),
+                None => {
+                    break Err(UnquoteError::new(
+                        UnquoteErrorKind::UnterminatedSingleQuote,
+                        start,
+                    ))
                 }
-                _ => (),
             }
-            sout.extend(literal);
-        };
-        let buf = &mut [0u8; 4];
-        for mode in esc {
-            use Char::*;
-            match mode {
-                Bell => push_literal(Outside, b"\\a"),
-                Backspace => push_literal(Outside, b"\\b"),
-                Escape => push_literal(Outside, b"\\e"),
-                FormFeed => push_literal(Outside, b"\\f"),
-                NewLine => push_literal(Outside, b"\\n"),
-                CarriageReturn => push_literal(Outside, b"\\r"),
-                HorizontalTab => push_literal(Outside, b"\\t"),
-                VerticalTab => push_literal(Outside, b"\\v"),
-                Control(ch) => push_literal(Outside, &u8_to_hex_escape_uppercase_x(ch)),
-                Backslash => push_literal(Whatever, b"\\\\"),
-                SingleQuote => push_literal(Whatever, b"\\'"),
-                DoubleQuote => push_literal(Inside, b"\""),
-                Delete => push_literal(Outside, b"\\X7F"),
-                PrintableInert(ch) => push_literal(Whatever, &ch.to_le_bytes()),
-                Printable(ch) => push_literal(Inside, &ch.to_le_bytes()),
-                Utf8(char) => push_literal(Inside, char.encode_utf8(buf).as_bytes()),
+        }
+    }
+
+    /// Unquote the remainder of a double-quoted string, the opening quote of
+    /// which was at `start`. Only `\"`, `\$`, `\\`, and backslash-newline are
+    /// escapes.
+    fn double_quoted(
+        cur: &mut Cursor,
+        start: usize,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        use UnquoteErrorKind::*;
+        loop {
+            match cur.next() {
+                Some((_, b'"')) => break Ok(()),
+                Some((_, b'\\')) => match cur.next() {
+                    Some((_, ch @ (b'"' | b'$' | b'\\'))) => sout.push(ch),
+                    Some((_, b'\n')) => (), // Line continuation.
+                    Some((_, ch)) => sout.extend([b'\\', ch]),
+                    None => break Err(UnquoteError::new(UnterminatedDoubleQuote, start)),
+                },
+                Some((pos, b'$')) => break Err(UnquoteError::new(Expansion(b'$'), pos)),
+                Some((_, ch)) => sout.push(ch),
+                None => break Err(UnquoteError::new(UnterminatedDoubleQuote, start)),
             }
         }
-        if inside_quotes_now {
-            sout.push(b'\'');
+    }
+
+    /// Unquote the escape sequence following an unquoted backslash at `start`.
+    fn backslashed(cur: &mut Cursor, start: usize, sout: &mut Vec<u8>) -> Result<(), UnquoteError> {
+        use UnquoteErrorKind::*;
+        let invalid = || UnquoteError::new(InvalidEscape, start);
+        let ch = match cur.next() {
+            Some((_, ch)) => ch,
+            None => return Err(UnquoteError::new(TrailingBackslash, start)),
+        };
+        match ch {
+            b'a' => sout.push(0x07),
+            b'b' => sout.push(0x08),
+            b'e' => sout.push(0x1B),
+            b'f' => sout.push(0x0C),
+            b'n' => sout.push(0x0A),
+            b'r' => sout.push(0x0D),
+            b't' => sout.push(0x09),
+            b'v' => sout.push(0x0B),
+            b'\n' => (), // Line continuation.
+            b'x' | b'X' => match digits(cur, 16, 2, 0) {
+                (_, 0) => return Err(invalid()),
+                (n, _) => sout.push(n as u8),
+            },
+            b'0'..=b'7' => match digits(cur, 8, 2, (ch - b'0').into()) {
+                (n, _) if n <= 0xFF => sout.push(n as u8),
+                _ => return Err(invalid()),
+            },
+            b'u' | b'U' => match digits(cur, 16, if ch == b'u' { 4 } else { 8 }, 0) {
+                (_, 0) => return Err(invalid()),
+                (n, _) => match char::from_u32(n) {
+                    Some(ch) => sout.extend(ch.encode_utf8(&mut [0u8; 4]).as_bytes()),
+                    None => return Err(invalid()),
+                },
+            },
+            b'c' => match cur.next() {
+                Some((_, ch @ (b'A'..=b'Z' | b'a'..=b'z' | b'[' | b'\\' | b']' | b'^' | b'_'))) => {
+                    sout.push(ch & 0x1F)
+                }
+                _ => return Err(invalid()),
+            },
+            // Anything else, including all the metacharacters, stands for
+            // itself when escaped.
+            ch => sout.push(ch),
         }
+        Ok(())
     }
 }
 
@@ -324,7 +839,7 @@ fn u8_to_hex_escape_uppercase_x(ch: u8) -> [u8; 4] {
 #[test]
 fn test_u8_to_hex_escape_uppercase_x() {
     for ch in u8::MIN..=u8::MAX {
-        let expected = format!("\\X{ch:02X}");
+        let expected = std::format!("\\X{ch:02X}");
         let observed = u8_to_hex_escape_uppercase_x(ch);
         let observed = std::str::from_utf8(&observed).unwrap();
         assert_eq!(observed, &expected);
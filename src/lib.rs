@@ -16,10 +16,15 @@
 //! [`Bash`]: `Bash`
 //! [`Fish`]: `Fish`
 //! [`Zsh`]: `Zsh`
+//! [`Shell`]: `Shell`
+//! [`Dialect`]: `Dialect`
 //!
 //! [`QuoteRefExt`]: `QuoteRefExt`
 //! [`QuoteRefExt::quoted`]: `QuoteRefExt::quoted`
 //! [`QuoteExt`]: `QuoteExt`
+//! [`QuoteWriteExt`]: `QuoteWriteExt`
+//! [`std::io::Write`]: `std::io::Write`
+//! [`core::fmt::Write`]: `core::fmt::Write`
 //!
 //! <style>
 //!   .readme-only { display: none; }
@@ -32,36 +37,82 @@
         feature = "bash",
         feature = "fish",
         feature = "sh",
+        feature = "std",
     ),
     doc = include_str!("../README.md")
 )]
+#![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
 use std::ffi::{OsStr, OsString};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
 use std::path::{Path, PathBuf};
 
 mod ascii;
 mod bash;
+mod command;
+mod compat;
+mod dialect;
+mod display;
+mod explain;
 mod fish;
 mod sh;
-mod utf8;
+mod shell;
+mod sink;
+mod style;
+mod unquote;
+mod word;
+mod zsh;
 
+pub use ascii::{first_unsafe_index, Char};
 #[cfg(feature = "bash")]
 pub use bash::Bash;
+#[cfg(all(
+    unix,
+    feature = "std",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+pub use command::{CommandExt, CommandSyntax, EnvClear};
+#[cfg(feature = "sh")]
+pub use compat::{Jq, Shellwords, Shlex};
+pub use dialect::{Dialect, Escaped, Quoting, Render};
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub use display::Quoted;
+pub use explain::{Explanation, Reason};
 #[cfg(feature = "fish")]
 pub use fish::Fish;
 #[cfg(feature = "sh")]
 pub use sh::Sh;
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub use shell::{ParseShellError, Shell};
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub use sink::BufferTooSmall;
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub use style::{Style, Styled};
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+pub use unquote::{UnquoteError, UnquoteErrorKind};
+#[cfg(feature = "alloc")]
+pub use word::{Renderer, Segment, ShellWord};
+#[cfg(feature = "bash")]
+pub use zsh::{Zsh, ZshFlag};
 
 /// Dash accepts the same quoted/escaped strings as `/bin/sh` – indeed, on many
 /// systems, `dash` _is_ `/bin/sh` – hence this is an alias for [`Sh`].
 #[cfg(feature = "sh")]
 pub type Dash = sh::Sh;
 
-/// Zsh accepts the same quoted/escaped strings as Bash, hence this is an alias
-/// for [`Bash`].
-#[cfg(feature = "bash")]
-pub type Zsh = bash::Bash;
-
 // ----------------------------------------------------------------------------
 
 /// Quoting/escaping a string of bytes into a shell-safe form.
@@ -83,6 +134,40 @@ pub trait Quote<OUT: Default>: QuoteInto<OUT> {
 /// Blanket [`Quote`] impl for anything that has a [`QuoteInto`] impl.
 impl<T: QuoteInto<OUT>, OUT: Default> Quote<OUT> for T {}
 
+/// Quoting/escaping many strings of bytes, separated by spaces, i.e. as a
+/// command line.
+///
+/// This is separate from [`QuoteInto`] because a separator cannot be written
+/// into an arbitrary `OUT`; existing implementations of [`QuoteInto`] are
+/// unaffected.
+pub trait JoinInto<OUT: ?Sized>: QuoteInto<OUT> {
+    /// Quote/escape many strings of bytes into an existing container,
+    /// separated by spaces, i.e. as a command line.
+    fn join_into<'q, I>(args: I, out: &mut OUT)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>;
+}
+
+/// Quoting/escaping many strings of bytes, separated by spaces, i.e. as a
+/// command line.
+pub trait Join<OUT: Default>: JoinInto<OUT> {
+    /// Quote/escape many strings of bytes into a new container, separated by
+    /// spaces, i.e. as a command line.
+    fn join<'q, I>(args: I) -> OUT
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        let mut out = OUT::default();
+        Self::join_into(args, &mut out);
+        out
+    }
+}
+
+/// Blanket [`Join`] impl for anything that has a [`JoinInto`] impl.
+impl<T: JoinInto<OUT>, OUT: Default> Join<OUT> for T {}
+
 // ----------------------------------------------------------------------------
 
 /// Extension trait for pushing shell quoted byte slices, e.g. `&[u8]`, [`&str`]
@@ -93,6 +178,13 @@ pub trait QuoteExt {
     where
         Q: QuoteInto<Self>,
         S: Into<Quotable<'q>>;
+
+    /// Push many quoted arguments, separated by spaces, i.e. as a command line.
+    fn push_quoted_join<'q, Q, I>(&mut self, _q: Q, args: I)
+    where
+        Q: JoinInto<Self>,
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>;
 }
 
 impl<T: ?Sized> QuoteExt for T {
@@ -103,6 +195,167 @@ impl<T: ?Sized> QuoteExt for T {
     {
         Q::quote_into(s, self);
     }
+
+    fn push_quoted_join<'q, Q, I>(&mut self, _q: Q, args: I)
+    where
+        Q: JoinInto<Self>,
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        Q::join_into(args, self);
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Quoting/escaping a string of bytes into an [`io::Write`], like a
+/// [`File`][`std::fs::File`], a pipe, or a socket.
+///
+/// Unlike [`QuoteInto`], output is written as it is produced, and I/O errors
+/// are returned rather than swallowed.
+#[cfg(feature = "std")]
+pub trait QuoteIntoWriter {
+    /// Quote/escape a string of bytes into a writer.
+    fn quote_into_writer<'q, S, W>(s: S, out: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'q>>,
+        W: io::Write + ?Sized;
+
+    /// Quote/escape many strings of bytes into a writer, separated by spaces,
+    /// i.e. as a command line.
+    fn join_into_writer<'q, I, W>(args: I, out: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+        W: io::Write + ?Sized;
+}
+
+/// Extension trait for writing shell quoted byte slices, e.g. `&[u8]`,
+/// [`&str`] – anything that's [`Quotable`] – into any [`io::Write`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "bash")] {
+/// # use shell_quote::{Bash, QuoteWriteExt};
+/// let mut script = Vec::new(); // Or a `File`, a pipe, etc.
+/// script.write_quoted(Bash, "foo bar").unwrap();
+/// assert_eq!(script, b"$'foo bar'");
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub trait QuoteWriteExt: io::Write {
+    fn write_quoted<'q, Q, S>(&mut self, _q: Q, s: S) -> io::Result<()>
+    where
+        Q: QuoteIntoWriter,
+        S: Into<Quotable<'q>>;
+
+    /// Write many quoted arguments, separated by spaces, i.e. as a command
+    /// line.
+    fn write_quoted_join<'q, Q, I>(&mut self, _q: Q, args: I) -> io::Result<()>
+    where
+        Q: QuoteIntoWriter,
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>;
+}
+
+#[cfg(feature = "std")]
+impl<T: io::Write + ?Sized> QuoteWriteExt for T {
+    fn write_quoted<'q, Q, S>(&mut self, _q: Q, s: S) -> io::Result<()>
+    where
+        Q: QuoteIntoWriter,
+        S: Into<Quotable<'q>>,
+    {
+        Q::quote_into_writer(s, self)
+    }
+
+    fn write_quoted_join<'q, Q, I>(&mut self, _q: Q, args: I) -> io::Result<()>
+    where
+        Q: QuoteIntoWriter,
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        Q::join_into_writer(args, self)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Object-safe quoting/escaping, for when the shell is chosen at runtime.
+///
+/// [`QuoteInto`] and [`Quote`] are generic over their inputs, and their
+/// functions take no `self`, so they cannot be used as trait objects. This
+/// trait can, e.g. to keep a registry of `Box<dyn Quoter>` in which custom
+/// quoters sit alongside the built-in ones.
+///
+/// Anything that implements [`Dialect`] – including [`Sh`], [`Bash`], and
+/// [`Fish`] – implements this too. Otherwise, implement
+/// [`quote_into_vec`][`Quoter::quote_into_vec`]; the other methods have
+/// default implementations built on it.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "bash", feature = "sh"))] {
+/// # use shell_quote::{Bash, Quoter, Sh};
+/// let quoters: Vec<(&str, Box<dyn Quoter>)> = vec![("sh", Box::new(Sh)), ("bash", Box::new(Bash))];
+/// for (name, quoter) in &quoters {
+///     let quoted = quoter.quote_vec("foo bar".into());
+///     println!("{name}: {}", String::from_utf8_lossy(&quoted));
+/// }
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub trait Quoter {
+    /// Quote/escape a string of bytes into an existing `Vec<u8>`.
+    fn quote_into_vec(&self, s: Quotable<'_>, out: &mut Vec<u8>);
+
+    /// Quote/escape a string of bytes into a new `Vec<u8>`.
+    fn quote_vec(&self, s: Quotable<'_>) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.quote_into_vec(s, &mut out);
+        out
+    }
+
+    /// Quote/escape many strings of bytes into an existing `Vec<u8>`,
+    /// separated by spaces, i.e. as a command line.
+    fn join_into_vec(&self, args: &[Quotable<'_>], out: &mut Vec<u8>) {
+        sink::infallible(dialect::join_into_sink(
+            args.iter().copied(),
+            out,
+            |s, out| {
+                self.quote_into_vec(s, out);
+                Ok(())
+            },
+        ))
+    }
+
+    /// Quote/escape a string of bytes into an [`io::Write`].
+    ///
+    /// By default this quotes into a temporary `Vec<u8>` which is then
+    /// written out in one go.
+    #[cfg(feature = "std")]
+    fn quote_into_writer(&self, s: Quotable<'_>, out: &mut dyn io::Write) -> io::Result<()> {
+        out.write_all(&self.quote_vec(s))
+    }
+}
+
+/// Bridge from [`Dialect`] into [`Quoter`]. Writers are written to as output
+/// is produced, as with [`QuoteIntoWriter`], rather than via a temporary.
+#[cfg(feature = "alloc")]
+impl<D: Dialect> Quoter for D {
+    fn quote_into_vec(&self, s: Quotable<'_>, out: &mut Vec<u8>) {
+        D::quote_into(s, out)
+    }
+
+    fn join_into_vec(&self, args: &[Quotable<'_>], out: &mut Vec<u8>) {
+        D::join_into(args.iter().copied(), out)
+    }
+
+    #[cfg(feature = "std")]
+    fn quote_into_writer(&self, s: Quotable<'_>, out: &mut dyn io::Write) -> io::Result<()> {
+        <D as QuoteIntoWriter>::quote_into_writer(s, out)
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -133,19 +386,22 @@ where
 /// [`AsRef<[u8]>`][`AsRef`] instead? The ergonomics of that approach were not
 /// so good. For example, quoting [`OsString`]/[`OsStr`] and
 /// [`PathBuf`]/[`Path`] didn't work in a natural way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Quotable<'a> {
-    #[cfg_attr(
-        not(any(feature = "bash", feature = "fish", feature = "sh")),
-        allow(unused)
-    )]
     Bytes(&'a [u8]),
-    #[cfg_attr(
-        not(any(feature = "bash", feature = "fish", feature = "sh")),
-        allow(unused)
-    )]
     Text(&'a str),
 }
 
+impl<'a> Quotable<'a> {
+    /// The string of bytes to be quoted, whether it was given as text or not.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match *self {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(text) => text.as_bytes(),
+        }
+    }
+}
+
 impl<'a> From<&'a [u8]> for Quotable<'a> {
     fn from(source: &'a [u8]) -> Quotable<'a> {
         Quotable::Bytes(source)
@@ -158,6 +414,7 @@ impl<'a, const N: usize> From<&'a [u8; N]> for Quotable<'a> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> From<&'a Vec<u8>> for Quotable<'a> {
     fn from(source: &'a Vec<u8>) -> Quotable<'a> {
         Quotable::Bytes(source)
@@ -170,13 +427,14 @@ impl<'a> From<&'a str> for Quotable<'a> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> From<&'a String> for Quotable<'a> {
     fn from(source: &'a String) -> Quotable<'a> {
         Quotable::Text(source)
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 impl<'a> From<&'a OsStr> for Quotable<'a> {
     fn from(source: &'a OsStr) -> Quotable<'a> {
         use std::os::unix::ffi::OsStrExt;
@@ -184,7 +442,7 @@ impl<'a> From<&'a OsStr> for Quotable<'a> {
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 impl<'a> From<&'a OsString> for Quotable<'a> {
     fn from(source: &'a OsString) -> Quotable<'a> {
         use std::os::unix::ffi::OsStrExt;
@@ -200,7 +458,7 @@ impl<'a> From<&'a bstr::BStr> for Quotable<'a> {
     }
 }
 
-#[cfg(feature = "bstr")]
+#[cfg(all(feature = "bstr", feature = "alloc"))]
 impl<'a> From<&'a bstr::BString> for Quotable<'a> {
     fn from(source: &'a bstr::BString) -> Quotable<'a> {
         let bytes: &[u8] = source.as_ref();
@@ -208,14 +466,14 @@ impl<'a> From<&'a bstr::BString> for Quotable<'a> {
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 impl<'a> From<&'a Path> for Quotable<'a> {
     fn from(source: &'a Path) -> Quotable<'a> {
         source.as_os_str().into()
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 impl<'a> From<&'a PathBuf> for Quotable<'a> {
     fn from(source: &'a PathBuf) -> Quotable<'a> {
         source.as_os_str().into()
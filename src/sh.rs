@@ -1,6 +1,37 @@
 #![cfg(feature = "sh")]
 
-use crate::{ascii::Char, Quotable, QuoteInto};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "alloc")]
+use crate::explain::{explain, Explanation};
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+#[cfg(feature = "alloc")]
+use crate::unquote::{
+    self, backslashed, check_bare, double_quoted, single_quoted, Cursor, UnquoteError,
+};
+#[cfg(feature = "alloc")]
+use crate::word::{self, Kind, Renderer, Segment, ShellWord};
+use crate::{
+    ascii::Char,
+    dialect::{
+        needs_quoting, quote_into_sink, quote_into_slice, quoted_len, Dialect, Escaped, Quoting,
+        Render,
+    },
+    sink::{BufferTooSmall, FmtSink},
+    style::{sealed::Stylable, Style, Styled},
+    Quotable, Quoted,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{join_into_sink, quote_cow},
+    sink::infallible,
+};
 
 /// Quote byte strings for use with `/bin/sh`.
 ///
@@ -19,6 +50,9 @@ use crate::{ascii::Char, Quotable, QuoteInto};
 /// [`String::from_utf8`]. The key difference is that `from_utf8` returns a
 /// [`Result`] which the caller must deal with.
 ///
+/// [`String`]: alloc::string::String
+/// [`String::from_utf8`]: alloc::string::String::from_utf8
+///
 /// # Compatibility
 ///
 /// Quoted/escaped strings produced by [`Sh`] also work in Bash, Dash, and Z
@@ -33,7 +67,7 @@ use crate::{ascii::Char, Quotable, QuoteInto};
 /// than as a command name, `%` is unremarkable in all three.
 ///
 /// The quoted/escaped strings it produces are different to those coming from
-/// [`Bash`][`crate::Bash`] or its alias [`Zsh`][`crate::Zsh`]. Those strings
+/// [`Bash`][`crate::Bash`] or [`Zsh`][`crate::Zsh`]. Those strings
 /// won't work in a pure `/bin/sh` shell like Dash, but they are better for
 /// humans to read, to copy and paste. For example, [`Sh`] does not (and cannot)
 /// escape control characters, but characters like `BEL` and `TAB` (and others)
@@ -101,31 +135,79 @@ use crate::{ascii::Char, Quotable, QuoteInto};
 #[derive(Debug, Clone, Copy)]
 pub struct Sh;
 
-impl QuoteInto<Vec<u8>> for Sh {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut Vec<u8>) {
-        Self::quote_into_vec(s, out);
+impl Dialect for Sh {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+
+    fn render(ch: Char) -> Render {
+        // Single quotes preserve everything but a single quote, which must be
+        // escaped outside of them. Bytes outside of ASCII cannot be escaped at
+        // all, so they are written as they are.
+        match ch {
+            Char::PrintableInert(_) | Char::Extended(_) | Char::Utf8(_) => {
+                Render::Literal(Quoting::Either)
+            }
+            Char::SingleQuote => Render::Escape(Quoting::Outside, Escaped::new(b"\\'")),
+            _ => Render::Literal(Quoting::Inside),
+        }
     }
-}
 
-#[cfg(unix)]
-impl QuoteInto<std::ffi::OsString> for Sh {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut std::ffi::OsString) {
-        use std::os::unix::ffi::OsStringExt;
-        let s = Self::quote_vec(s);
-        let s = std::ffi::OsString::from_vec(s);
-        out.push(s);
+    fn literal_run(sin: &[u8]) -> usize {
+        // Inside single quotes, only a single quote is special, so skip ahead
+        // to the next one, or to the end.
+        find_single_quote(sin).unwrap_or(sin.len())
     }
 }
 
-#[cfg(feature = "bstr")]
-impl QuoteInto<bstr::BString> for Sh {
-    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut bstr::BString) {
-        let s = Self::quote_vec(s);
-        out.extend(s);
+#[cfg(feature = "alloc")]
+impl Renderer for Sh {
+    fn render_segment(segment: &Segment<'_>, out: &mut Vec<u8>) {
+        match *segment {
+            Segment::Bare(s) => out.extend_from_slice(s.as_bytes()),
+            Segment::SingleQuoted(s) => word::single_quoted(s.as_bytes(), out),
+            Segment::DoubleQuoted(s) => word::double_quoted(s.as_bytes(), b"$`\"\\", out),
+            // There's no ANSI-C quoting in `/bin/sh`, so quote it as anything
+            // else would be quoted.
+            Segment::AnsiC(s) => infallible(quote_into_sink::<Self, _>(s, out)),
+            // A backslash preserves the byte after it, except a newline, which
+            // it removes. Bytes outside of ASCII cannot be escaped at all.
+            Segment::Escape(b'\n') => out.extend_from_slice(b"'\n'"),
+            Segment::Escape(byte @ 0x80..) => out.push(byte),
+            Segment::Escape(byte) => out.extend_from_slice(&[b'\\', byte]),
+        }
     }
 }
 
+impl Stylable for Sh {
+    type Plain = Self;
+}
+
 impl Sh {
+    /// How [`Sh`] quotes: only the characters that need it, in plain single
+    /// quotes.
+    pub const STYLE: Style = Style {
+        prefer_plain_single_quotes: true,
+        whole_word: false,
+    };
+
+    /// Quote in a different [`Style`], e.g. quoting the whole word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use shell_quote::{Sh, Style};
+    /// let sh = Sh::with_style(Style { whole_word: true, ..Sh::STYLE });
+    /// assert_eq!(sh.quote_vec("foo bar"), b"'foo bar'");
+    /// assert_eq!(sh.quote_vec("it's here"), b"'it'\\''s here'");
+    /// assert_eq!(Sh::with_style(Sh::STYLE).quote_vec("it's here"), Sh::quote_vec("it's here"));
+    /// # }
+    /// ```
+    ///
+    pub const fn with_style(style: Style) -> Styled<Self> {
+        Styled::new(style)
+    }
+
     /// Quote a string of bytes into a new `Vec<u8>`.
     ///
     /// This will return one of the following:
@@ -143,24 +225,11 @@ impl Sh {
     /// assert_eq!(Sh::quote_vec("foo bar"), b"foo' bar'");
     /// ```
     ///
+    #[cfg(feature = "alloc")]
     pub fn quote_vec<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
-        let bytes = match s.into() {
-            Quotable::Bytes(bytes) => bytes,
-            Quotable::Text(s) => s.as_bytes(),
-        };
-        match escape_prepare(bytes) {
-            Prepared::Empty => vec![b'\'', b'\''],
-            Prepared::Inert => bytes.into(),
-            Prepared::Escape(esc) => {
-                // Here, previously, an optimisation precalculated the required
-                // capacity of the output `Vec` to avoid reallocations later on,
-                // but benchmarks showed that it was slower. It _may_ have
-                // lowered maximum RAM required, but that was not measured.
-                let mut sout = Vec::new();
-                escape_chars(esc, &mut sout);
-                sout
-            }
-        }
+        let mut sout = Vec::new();
+        Self::quote_into_vec(s, &mut sout);
+        sout
     }
 
     /// Quote a string of bytes into an existing `Vec<u8>`.
@@ -178,81 +247,473 @@ impl Sh {
     /// assert_eq!(buf, b"foobar foo' bar'");
     /// ```
     ///
+    #[cfg(feature = "alloc")]
     pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(s: S, sout: &mut Vec<u8>) {
+        infallible(quote_into_sink::<Self, _>(s.into(), sout))
+    }
+
+    /// Quote a string of bytes, borrowing it if no quoting is necessary.
+    ///
+    /// This returns the same as [`quote_vec`][`Self::quote_vec`], but
+    /// allocates only when something needed quoting/escaping. An empty string
+    /// is quoted as `''`, which is borrowed too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use shell_quote::Sh;
+    /// assert_eq!(Sh::quote_cow("foobar"), Cow::Borrowed(b"foobar"));
+    /// assert_eq!(Sh::quote_cow("foo bar"), Cow::<[u8]>::Owned(b"foo' bar'".to_vec()));
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, [u8]> {
+        quote_cow::<Self>(s.into())
+    }
+
+    /// Check whether a string of bytes needs quoting/escaping, i.e. whether
+    /// [`quote_vec`][`Self::quote_vec`] would return anything other than the
+    /// string as it is.
+    ///
+    /// This is cheap: it stops at the first byte that needs quoting, and it
+    /// writes nothing. Note that an empty string needs quoting, as `''`. Use
+    /// [`first_unsafe_index`][`crate::first_unsafe_index`] to find out where
+    /// quoting starts, and [`Char`][`crate::Char`] to find out why.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// assert!(!Sh::needs_quoting("foo/bar-1.2"));
+    /// assert!(Sh::needs_quoting("foo bar"));
+    /// assert!(Sh::needs_quoting(""));
+    /// assert!(!Sh::needs_quoting("café")); // Written as it is.
+    /// ```
+    ///
+    pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(s: S) -> bool {
+        needs_quoting::<Self>(s.into())
+    }
+
+    /// Explain why a string of bytes needs quoting/escaping, character by
+    /// character.
+    ///
+    /// There is an [`Explanation`] for each character that is quoted or
+    /// escaped, in order, saying where it is, why it must be quoted, and how
+    /// it is written. It is empty if the string needs no quoting, and also if
+    /// the string is empty, though that is quoted as `''`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Sh, Reason};
+    /// let why = Sh::explain("FOO=it's");
+    /// assert_eq!(why.len(), 2);
+    /// assert_eq!(why[0].reason(), Reason::Assignment);
+    /// assert_eq!(why[0].to_string(), "`=` at 3: may start an assignment");
+    /// assert_eq!(why[1].to_string(), "`'` at 6: quote, rendered as `\\'`");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn explain<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<Explanation> {
+        explain::<Self>(s.into())
+    }
+
+    /// Plan how to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// Rendering the plan with [`Sh`] gives the same output as
+    /// [`quote_vec`][`Self::quote_vec`]; it can also be inspected, changed,
+    /// or rendered for another shell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Quotable, Segment, Sh};
+    /// let word = Sh::plan("foo bar");
+    /// assert_eq!(
+    ///     word.segments(),
+    ///     [
+    ///         Segment::Bare(Quotable::Text("foo")),
+    ///         Segment::SingleQuoted(Quotable::Text(" bar")),
+    ///     ]
+    /// );
+    /// assert_eq!(word.render::<Sh>(), b"foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan::<Self>(s.into(), Segment::SingleQuoted)
+    }
+
+    /// Plan the shortest way to quote a string of bytes, as a [`ShellWord`].
+    ///
+    /// This considers escaping characters with a backslash, single quotes,
+    /// and double quotes, and picks whichever gives the fewest bytes when
+    /// rendered with [`Sh`]. It is slower than [`plan`][`Self::plan`], and
+    /// its choices may change between releases; use it where size matters
+    /// more than a stable output.
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn plan_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan_shortest::<Self>(
+            s.into(),
+            &[
+                Kind::Bare,
+                Kind::SingleQuoted,
+                Kind::DoubleQuoted,
+                Kind::Escape,
+            ],
+        )
+    }
+
+    /// Quote a string of bytes into a new `Vec<u8>`, as briefly as possible.
+    ///
+    /// This renders the plan from [`plan_shortest`][`Self::plan_shortest`].
+    /// It is never longer than [`quote_vec`][`Self::quote_vec`], and often
+    /// shorter, but it is not the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// assert_eq!(Sh::quote_vec_shortest("Bob's Files"), br#""Bob's Files""#);
+    /// assert_eq!(Sh::quote_vec("Bob's Files"), b"Bob\\'s' Files'");
+    /// assert_eq!(Sh::quote_vec_shortest("foo bar"), b"foo\\ bar");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        Self::plan_shortest(s).render::<Self>()
+    }
+
+    /// Calculate the exact length, in bytes, of the quoted form of a string
+    /// of bytes, i.e. the length of what [`quote_vec`][`Self::quote_vec`]
+    /// would return.
+    ///
+    /// This does the work of quoting without keeping the output, so it is
+    /// about as costly as quoting. Use it to size a buffer up front, e.g. for
+    /// [`quote_into_slice`][`Self::quote_into_slice`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// assert_eq!(Sh::quoted_len("foobar"), 6);
+    /// assert_eq!(Sh::quoted_len("foo bar"), 9); // foo' bar'
+    /// ```
+    ///
+    pub fn quoted_len<'a, S: Into<Quotable<'a>>>(s: S) -> usize {
+        quoted_len::<Self>(s.into())
+    }
+
+    /// Quote a string of bytes into a caller-provided slice, without
+    /// allocating.
+    ///
+    /// On success, returns the number of bytes written to the start of
+    /// `sout`; these are the same bytes that [`quote_vec`][`Self::quote_vec`]
+    /// would return. If `sout` is too small, returns [`BufferTooSmall`], from
+    /// which the length needed can be found; the contents of `sout` are then
+    /// unspecified. Use [`quoted_len`][`Self::quoted_len`] to find the length
+    /// needed beforehand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut buf = [0u8; 16];
+    /// let len = Sh::quote_into_slice("foo bar", &mut buf)?;
+    /// assert_eq!(&buf[..len], b"foo' bar'");
+    /// let err = Sh::quote_into_slice("foo bar", &mut buf[..4]).unwrap_err();
+    /// assert_eq!(err.needed(), 9);
+    /// # Ok::<(), shell_quote::BufferTooSmall>(())
+    /// ```
+    ///
+    pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut [u8],
+    ) -> Result<usize, BufferTooSmall> {
+        quote_into_slice::<Self>(s.into(), sout)
+    }
+
+    /// Quote a string into a [`fmt::Write`], like a [`String`] or a
+    /// [`fmt::Formatter`].
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but without an intermediate buffer. It fails only if `sout`
+    /// does.
+    ///
+    /// Unlike [`Bash`][`crate::Bash`] and [`Fish`][`crate::Fish`], `/bin/sh`
+    /// has no way to escape bytes that are not valid UTF-8 – they are passed
+    /// through as-is – so this accepts only text.
+    ///
+    /// [`String`]: alloc::string::String
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut buf = String::from("cat ");
+    /// Sh::quote_into_fmt("foo bar", &mut buf).unwrap();
+    /// assert_eq!(buf, "cat foo' bar'");
+    /// ```
+    ///
+    pub fn quote_into_fmt<W: fmt::Write + ?Sized>(s: &str, sout: &mut W) -> fmt::Result {
+        quote_into_sink::<Self, _>(s.into(), &mut FmtSink(sout))
+    }
+
+    /// Quote a string lazily, for use with `format!`, `write!`, logging, etc.
+    ///
+    /// The returned [`Quoted`] implements [`Display`][`fmt::Display`] and
+    /// [`Debug`][`fmt::Debug`], writing what
+    /// [`quote_into_vec`][`Self::quote_into_vec`] would straight into the
+    /// formatter. Nothing is allocated. As with
+    /// [`quote_into_fmt`][`Self::quote_into_fmt`], this accepts only text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let arg = "foo bar";
+    /// assert_eq!(format!("cat {}", Sh::display(arg)), "cat foo' bar'");
+    /// assert_eq!(format!("{:?}", Sh::display(arg)), "foo' bar'");
+    /// ```
+    ///
+    pub fn display(s: &str) -> Quoted<'_, Self> {
+        Quoted::new(s.into())
+    }
+
+    /// Quote many strings of bytes into a new `Vec<u8>`, separated by spaces,
+    /// i.e. as a command line.
+    ///
+    /// Each argument is quoted as by [`quote_vec`][`Self::quote_vec`], so an
+    /// empty argument becomes `''`. No arguments at all produces an empty
+    /// `Vec`.
+    ///
+    /// See [`join_into_vec`][`Self::join_into_vec`] for a variant that extends
+    /// an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// assert_eq!(Sh::join_vec(["ls", "foo bar", ""]), b"ls foo' bar' ''");
+    /// assert_eq!(Sh::join_vec(Vec::<&str>::new()), b"");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        Self::join_into_vec(args, &mut sout);
+        sout
+    }
+
+    /// Quote many strings of bytes into an existing `Vec<u8>`, separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] for more details. Note that no space
+    /// is pushed _before_ the first argument.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut buf = b"exec ".to_vec();
+    /// Sh::join_into_vec(["ls", "foo bar", ""], &mut buf);
+    /// assert_eq!(buf, [&b"exec "[..], b"ls foo' bar' ''"].concat());
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn join_into_vec<'a, I>(args: I, sout: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        infallible(join_into_sink(args, sout, quote_into_sink::<Self, _>))
+    }
+
+    /// Quote a string of bytes into an [`io::Write`], like a
+    /// [`File`][`std::fs::File`] or a pipe.
+    ///
+    /// This writes exactly what [`quote_into_vec`][`Self::quote_into_vec`]
+    /// would, but as it goes, without an intermediate buffer. I/O errors are
+    /// returned. Output is written in many small pieces, so consider wrapping
+    /// an unbuffered writer in an [`io::BufWriter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut script = Vec::new(); // Or a `File`, a pipe, etc.
+    /// Sh::quote_into_writer("foo bar", &mut script).unwrap();
+    /// assert_eq!(script, b"foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn quote_into_writer<'a, S, W>(s: S, sout: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut IoSink(sout))
+    }
+
+    /// Quote many strings of bytes into an [`io::Write`], separated by
+    /// spaces, i.e. as a command line.
+    ///
+    /// See [`join_vec`][`Self::join_vec`] and
+    /// [`quote_into_writer`][`Self::quote_into_writer`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut script = b"exec ".to_vec();
+    /// Sh::join_into_writer(["ls", "foo bar"], &mut script).unwrap();
+    /// assert_eq!(script, b"exec ls foo' bar'");
+    /// ```
+    ///
+    #[cfg(feature = "std")]
+    pub fn join_into_writer<'a, I, W>(args: I, sout: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(sout), quote_into_sink::<Self, _>)
+    }
+
+    /// Unquote a string of bytes quoted for `/bin/sh` into a new `Vec<u8>`.
+    ///
+    /// This is the inverse of [`quote_vec`][`Self::quote_vec`], but it also
+    /// understands input written by humans: single quotes, double quotes, and
+    /// backslash escapes, in any combination, following the rules quoted from
+    /// dash(1) in the documentation for [`Sh`].
+    ///
+    /// The input must be a single word that stands for a literal string. An
+    /// [`UnquoteError`] is returned for unterminated quotes, a trailing
+    /// backslash, an unquoted metacharacter – including whitespace – or an
+    /// expansion, like `$HOME`, since those mean something to the shell that
+    /// cannot be represented here.
+    ///
+    /// See [`unquote_into_vec`][`Self::unquote_into_vec`] for a variant that
+    /// extends an existing `Vec` instead of allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Sh, UnquoteErrorKind};
+    /// assert_eq!(Sh::unquote("foo' bar'").unwrap(), b"foo bar");
+    /// assert_eq!(Sh::unquote(r#"it\'s" \"ok\"""#).unwrap(), b"it's \"ok\"");
+    /// let err = Sh::unquote("foo bar").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Metacharacter(b' '));
+    /// assert_eq!(err.position(), 3);
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<u8>, UnquoteError> {
+        let mut sout = Vec::new();
+        Self::unquote_into_vec(s, &mut sout)?;
+        Ok(sout)
+    }
+
+    /// Unquote a string of bytes quoted for `/bin/sh` into an existing
+    /// `Vec<u8>`.
+    ///
+    /// See [`unquote`][`Self::unquote`] for more details. On error, `sout` is
+    /// left as it was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Sh;
+    /// let mut buf = Vec::with_capacity(128);
+    /// Sh::unquote_into_vec("foo' bar'", &mut buf).unwrap();
+    /// assert_eq!(buf, b"foo bar");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote_into_vec<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
         let bytes = match s.into() {
             Quotable::Bytes(bytes) => bytes,
             Quotable::Text(s) => s.as_bytes(),
         };
-        match escape_prepare(bytes) {
-            Prepared::Empty => sout.extend(b"''"),
-            Prepared::Inert => sout.extend(bytes),
-            Prepared::Escape(esc) => {
-                // Here, previously, an optimisation precalculated the required
-                // capacity of the output `Vec` to avoid reallocations later on,
-                // but benchmarks showed that it was slower. It _may_ have
-                // lowered maximum RAM required, but that was not measured.
-                escape_chars(esc, sout);
-            }
-        }
+        unquote::unquote(bytes, sout, read_word)
+    }
+
+    /// Split a command line quoted for `/bin/sh` into words, unquoting each one.
+    ///
+    /// This is the inverse of quoting each argument and joining them with
+    /// spaces, and it understands the same quoting and escaping as
+    /// [`unquote`][`Self::unquote`]. Words are separated by unquoted spaces and
+    /// tabs, and a `#` at the start of a word begins a comment that runs to
+    /// the end of the line.
+    ///
+    /// Rather than pass them through, an [`UnquoteError`] is returned for
+    /// anything that cannot be represented as a plain list of arguments:
+    /// control operators and redirections like `|`, `;`, `&&`, and `>`, which
+    /// are reported as [`UnquoteErrorKind::Operator`]; expansions like `$HOME`
+    /// and `*.txt`; and any further command after a newline.
+    ///
+    /// [`UnquoteErrorKind::Operator`]: crate::UnquoteErrorKind::Operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Sh, UnquoteErrorKind};
+    /// let words = Sh::split("rsync -av 'my dir'/ it\\'s # sync").unwrap();
+    /// assert_eq!(words, vec![&b"rsync"[..], b"-av", b"my dir/", b"it's"]);
+    /// let err = Sh::split("ls | wc").unwrap_err();
+    /// assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'|'));
+    /// assert_eq!(err.position(), 3);
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn split<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<Vec<u8>>, UnquoteError> {
+        let bytes = match s.into() {
+            Quotable::Bytes(bytes) => bytes,
+            Quotable::Text(s) => s.as_bytes(),
+        };
+        unquote::split(bytes, read_word)
     }
 }
 
 // ----------------------------------------------------------------------------
 
-enum Prepared {
-    Empty,
-    Inert,
-    Escape(Vec<Char>),
+/// Find the first single quote in `sin`.
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_single_quote(sin: &[u8]) -> Option<usize> {
+    sin.iter().position(|&ch| ch == b'\'')
 }
 
-fn escape_prepare(sin: &[u8]) -> Prepared {
-    let esc: Vec<_> = sin.iter().map(Char::from).collect();
-    // An optimisation: if the string is not empty and contains only "safe"
-    // characters we can avoid further work.
-    if esc.is_empty() {
-        Prepared::Empty
-    } else if esc.iter().all(Char::is_inert) {
-        Prepared::Inert
-    } else {
-        Prepared::Escape(esc)
-    }
+/// Find the first single quote in `sin`, with [`memchr`]'s vectorised search.
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_single_quote(sin: &[u8]) -> Option<usize> {
+    memchr::memchr(b'\'', sin)
 }
 
-fn escape_chars(esc: Vec<Char>, sout: &mut Vec<u8>) {
-    let mut inside_quotes = false;
-    for mode in esc {
-        use Char::*;
-        match mode {
-            PrintableInert(ch) | Extended(ch) => sout.push(ch),
-            Control(ch) | Printable(ch) => {
-                if inside_quotes {
-                    sout.push(ch);
-                } else {
-                    sout.push(b'\'');
-                    inside_quotes = true;
-                    sout.push(ch);
-                }
-            }
-            SingleQuote => {
-                if inside_quotes {
-                    sout.extend(b"'\\'");
-                    inside_quotes = false;
-                } else {
-                    sout.extend(b"\\'");
-                }
-            }
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "alloc")]
+fn read_word(cur: &mut Cursor, sout: &mut Vec<u8>) -> Result<(), UnquoteError> {
+    while let Some((pos, ch)) = cur.next_in_word() {
+        match ch {
+            b'\'' => single_quoted(cur, pos, sout)?,
+            b'"' => double_quoted(cur, pos, sout)?,
+            b'\\' => backslashed(cur, pos, sout)?,
             ch => {
-                if inside_quotes {
-                    sout.push(ch.code());
-                } else {
-                    sout.push(b'\'');
-                    inside_quotes = true;
-                    sout.push(ch.code());
-                }
+                check_bare(cur, ch, pos)?;
+                sout.push(ch);
             }
         }
     }
-    if inside_quotes {
-        sout.push(b'\'');
-    }
+    Ok(())
 }
@@ -0,0 +1,367 @@
+#![cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+
+//! Choosing a shell at runtime.
+
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{join_into_sink, quote_into_sink},
+    explain::explain,
+    sink::{infallible, Sink},
+    Explanation, Quoter,
+};
+use crate::{
+    dialect::{needs_quoting, quote_into_slice, quoted_len},
+    sink::BufferTooSmall,
+    Quotable,
+};
+
+/// A shell chosen at runtime, e.g. from a command-line flag or from `$SHELL`.
+///
+/// [`Sh`][`crate::Sh`], [`Bash`][`crate::Bash`], and [`Fish`][`crate::Fish`]
+/// are chosen at compile time. [`Shell`] dispatches to one of them according to
+/// its value, and has methods of the same names: [`quote_vec`], [`join_vec`],
+/// etc. It does not implement [`QuoteInto`][`crate::QuoteInto`] because the
+/// functions of that trait take no `self`, and so cannot know which shell to
+/// quote for; instead, there is a method for each type that `QuoteInto` quotes
+/// into, like [`quote_into_vec`] and [`quote_into_os_string`]. There is none
+/// for `String`, since [`Sh`][`crate::Sh`] does not quote into one: its output
+/// may not be valid UTF-8.
+///
+/// [`quote_vec`]: Self::quote_vec
+/// [`join_vec`]: Self::join_vec
+/// [`quote_into_vec`]: Self::quote_into_vec
+/// [`quote_into_os_string`]: Self::quote_into_os_string
+///
+/// There is a variant for each shell that the enabled features support.
+/// [`Dash`][`Shell::Dash`] quotes as [`Sh`][`Shell::Sh`] does; it is here so
+/// that a shell, once parsed, can be named again correctly.
+/// [`Zsh`][`Shell::Zsh`] quotes as [`Zsh`][`crate::Zsh`] does.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "alloc", feature = "bash", feature = "fish"))] {
+/// # use shell_quote::Shell;
+/// let shell: Shell = "/usr/bin/zsh".parse().unwrap();
+/// assert_eq!(shell, Shell::Zsh);
+/// assert_eq!(shell.quote_vec("foo bar"), b"$'foo bar'");
+/// let shell: Shell = "fish".parse().unwrap();
+/// assert_eq!(shell.join_vec(["echo", "foo bar"]), b"echo foo' bar'");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Shell {
+    /// `/bin/sh`, and POSIX shells like ash, ksh, and mksh.
+    #[cfg(feature = "sh")]
+    Sh,
+    /// [Dash](https://en.wikipedia.org/wiki/Almquist_shell#dash).
+    #[cfg(feature = "sh")]
+    Dash,
+    /// [GNU Bash](https://www.gnu.org/software/bash/).
+    #[cfg(feature = "bash")]
+    Bash,
+    /// [Z Shell](https://zsh.sourceforge.io/).
+    #[cfg(feature = "bash")]
+    Zsh,
+    /// [fish](https://fishshell.com/).
+    #[cfg(feature = "fish")]
+    Fish,
+}
+
+/// The shell to fall back to when none is recognised: the lowest common
+/// denominator of those that are enabled.
+#[cfg(feature = "sh")]
+const FALLBACK: Shell = Shell::Sh;
+#[cfg(all(not(feature = "sh"), feature = "bash"))]
+const FALLBACK: Shell = Shell::Bash;
+#[cfg(not(any(feature = "sh", feature = "bash")))]
+const FALLBACK: Shell = Shell::Fish;
+
+/// Evaluate `$body` with `$D` standing for the [`Dialect`] that quotes for
+/// `$shell`. Every method of [`Shell`] that depends on the dialect goes
+/// through here, so this is the one place that lists them.
+///
+/// [`Dialect`]: crate::Dialect
+macro_rules! with_dialect {
+    ($shell:expr, $D:ident => $body:expr) => {
+        match $shell {
+            #[cfg(feature = "sh")]
+            Shell::Sh | Shell::Dash => {
+                type $D = crate::Sh;
+                $body
+            }
+            #[cfg(feature = "bash")]
+            Shell::Bash => {
+                type $D = crate::Bash;
+                $body
+            }
+            #[cfg(feature = "bash")]
+            Shell::Zsh => {
+                type $D = crate::Zsh;
+                $body
+            }
+            #[cfg(feature = "fish")]
+            Shell::Fish => {
+                type $D = crate::Fish;
+                $body
+            }
+        }
+    };
+}
+
+impl Shell {
+    /// The shell named by the `SHELL` environment variable, or a fallback if
+    /// it is not set or not recognised.
+    ///
+    /// The fallback is [`Shell::Sh`], or [`Shell::Bash`] if the `sh` feature
+    /// is disabled, or otherwise [`Shell::Fish`]. See [`Shell::from_str`] for
+    /// the names that are recognised.
+    ///
+    /// Note that `SHELL` is the user's _login_ shell, which may not be the
+    /// shell that is running, nor the shell that will run a script.
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Self {
+        std::env::var_os("SHELL")
+            .and_then(|shell| shell.to_str()?.parse().ok())
+            .unwrap_or(FALLBACK)
+    }
+
+    /// The name of this shell, e.g. `bash`.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "sh")]
+            Shell::Sh => "sh",
+            #[cfg(feature = "sh")]
+            Shell::Dash => "dash",
+            #[cfg(feature = "bash")]
+            Shell::Bash => "bash",
+            #[cfg(feature = "bash")]
+            Shell::Zsh => "zsh",
+            #[cfg(feature = "fish")]
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// Quote a string of bytes into a new `Vec<u8>`.
+    ///
+    /// See, for example, [`Bash::quote_vec`][`crate::Bash::quote_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec<'a, S: Into<Quotable<'a>>>(self, s: S) -> Vec<u8> {
+        let mut sout = Vec::new();
+        self.quote_into_vec(s, &mut sout);
+        sout
+    }
+
+    /// Quote a string of bytes into an existing `Vec<u8>`.
+    #[cfg(feature = "alloc")]
+    pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(self, s: S, sout: &mut Vec<u8>) {
+        infallible(self.quote_into_sink(s.into(), sout))
+    }
+
+    /// Quote a string of bytes into an existing [`OsString`].
+    ///
+    /// [`OsString`]: std::ffi::OsString
+    #[cfg(all(unix, feature = "std"))]
+    pub fn quote_into_os_string<'a, S: Into<Quotable<'a>>>(
+        self,
+        s: S,
+        sout: &mut std::ffi::OsString,
+    ) {
+        use std::os::unix::ffi::OsStringExt;
+        sout.push(std::ffi::OsString::from_vec(self.quote_vec(s)));
+    }
+
+    /// Quote a string of bytes into an existing [`BString`].
+    ///
+    /// [`BString`]: bstr::BString
+    #[cfg(all(feature = "bstr", feature = "alloc"))]
+    pub fn quote_into_bstring<'a, S: Into<Quotable<'a>>>(self, s: S, sout: &mut bstr::BString) {
+        self.quote_into_vec(s, sout)
+    }
+
+    /// Check whether a string of bytes needs quoting/escaping.
+    ///
+    /// See, for example, [`Bash::needs_quoting`][`crate::Bash::needs_quoting`].
+    pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(self, s: S) -> bool {
+        with_dialect!(self, D => needs_quoting::<D>(s.into()))
+    }
+
+    /// Explain why a string of bytes needs quoting/escaping, character by
+    /// character.
+    ///
+    /// See, for example, [`Bash::explain`][`crate::Bash::explain`].
+    #[cfg(feature = "alloc")]
+    pub fn explain<'a, S: Into<Quotable<'a>>>(self, s: S) -> Vec<Explanation> {
+        with_dialect!(self, D => explain::<D>(s.into()))
+    }
+
+    /// Calculate the exact length, in bytes, of the quoted form of a string
+    /// of bytes.
+    ///
+    /// See, for example, [`Bash::quoted_len`][`crate::Bash::quoted_len`].
+    pub fn quoted_len<'a, S: Into<Quotable<'a>>>(self, s: S) -> usize {
+        with_dialect!(self, D => quoted_len::<D>(s.into()))
+    }
+
+    /// Quote a string of bytes into a caller-provided slice, without
+    /// allocating.
+    ///
+    /// See, for example,
+    /// [`Bash::quote_into_slice`][`crate::Bash::quote_into_slice`].
+    pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+        self,
+        s: S,
+        sout: &mut [u8],
+    ) -> Result<usize, BufferTooSmall> {
+        with_dialect!(self, D => quote_into_slice::<D>(s.into(), sout))
+    }
+
+    /// Quote many strings of bytes into a new `Vec<u8>`, separated by spaces,
+    /// i.e. as a command line.
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(self, args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        self.join_into_vec(args, &mut sout);
+        sout
+    }
+
+    /// Quote many strings of bytes into an existing `Vec<u8>`, separated by
+    /// spaces, i.e. as a command line.
+    #[cfg(feature = "alloc")]
+    pub fn join_into_vec<'a, I>(self, args: I, sout: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        infallible(join_into_sink(args, sout, |s, sout| {
+            self.quote_into_sink(s, sout)
+        }))
+    }
+
+    /// Quote a string of bytes into an [`io::Write`].
+    ///
+    /// See, for example,
+    /// [`Bash::quote_into_writer`][`crate::Bash::quote_into_writer`].
+    #[cfg(feature = "std")]
+    pub fn quote_into_writer<'a, S, W>(self, s: S, sout: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        self.quote_into_sink(s.into(), &mut IoSink(sout))
+    }
+
+    /// Quote many strings of bytes into an [`io::Write`], separated by spaces,
+    /// i.e. as a command line.
+    #[cfg(feature = "std")]
+    pub fn join_into_writer<'a, I, W>(self, args: I, sout: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(sout), |s, sout| {
+            self.quote_into_sink(s, sout)
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    fn quote_into_sink<S: Sink>(self, s: Quotable, sout: &mut S) -> Result<(), S::Error> {
+        with_dialect!(self, D => quote_into_sink::<D, _>(s, sout))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Quoter for Shell {
+    fn quote_into_vec(&self, s: Quotable<'_>, out: &mut Vec<u8>) {
+        Shell::quote_into_vec(*self, s, out)
+    }
+
+    #[cfg(feature = "std")]
+    fn quote_into_writer(&self, s: Quotable<'_>, out: &mut dyn io::Write) -> io::Result<()> {
+        Shell::quote_into_writer(*self, s, out)
+    }
+}
+
+impl Default for Shell {
+    /// The same fallback as [`Shell::from_env`] uses.
+    fn default() -> Self {
+        FALLBACK
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shell {
+    type Err = ParseShellError;
+
+    /// Recognise a shell from its name, e.g. `bash`, or from a path to it,
+    /// e.g. `/usr/local/bin/bash`, as found in `$SHELL`.
+    ///
+    /// Case is ignored, as are a leading `-` – which marks a login shell in
+    /// `$0` – and a trailing `.exe`. POSIX shells without a variant of their
+    /// own – `ash`, `ksh`, and `mksh` – are recognised as [`Shell::Sh`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.rsplit(['/', '\\']).next().unwrap_or(s);
+        let name = name.strip_prefix('-').unwrap_or(name);
+        let name = match name.len().checked_sub(4) {
+            Some(end) if name.as_bytes()[end..].eq_ignore_ascii_case(b".exe") => &name[..end],
+            _ => name,
+        };
+        let known: &[(&str, Shell)] = &[
+            #[cfg(feature = "sh")]
+            ("sh", Shell::Sh),
+            #[cfg(feature = "sh")]
+            ("ash", Shell::Sh),
+            #[cfg(feature = "sh")]
+            ("ksh", Shell::Sh),
+            #[cfg(feature = "sh")]
+            ("mksh", Shell::Sh),
+            #[cfg(feature = "sh")]
+            ("dash", Shell::Dash),
+            #[cfg(feature = "bash")]
+            ("bash", Shell::Bash),
+            #[cfg(feature = "bash")]
+            ("zsh", Shell::Zsh),
+            #[cfg(feature = "fish")]
+            ("fish", Shell::Fish),
+        ];
+        known
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|&(_, shell)| shell)
+            .ok_or(ParseShellError(()))
+    }
+}
+
+/// The error returned when parsing a [`Shell`] from a name that is not
+/// recognised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseShellError(());
+
+impl fmt::Display for ParseShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unrecognised shell")
+    }
+}
+
+impl core::error::Error for ParseShellError {}
@@ -0,0 +1,159 @@
+#![cfg_attr(
+    not(any(feature = "bash", feature = "fish", feature = "sh")),
+    allow(dead_code)
+)]
+
+//! Destinations for quoted/escaped output.
+//!
+//! The quoting code in [`crate::dialect`] writes into a [`Sink`] rather than
+//! directly into a `Vec<u8>` so that the same code can stream into other
+//! things, like a [`fmt::Formatter`] or a file, without allocating along the way.
+
+use core::convert::Infallible;
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+
+pub(crate) trait Sink {
+    type Error;
+
+    /// Write all of `bytes`.
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "alloc")]
+impl Sink for Vec<u8> {
+    type Error = Infallible;
+
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Adapts a [`fmt::Write`] into a [`Sink`].
+///
+/// Each call to [`put`][`Sink::put`] must write a whole number of UTF-8
+/// characters, otherwise it fails with [`fmt::Error`]. The quoting code takes
+/// care to do this when given text, and when it hex-escapes bytes the output
+/// is ASCII.
+pub(crate) struct FmtSink<'a, W: ?Sized>(pub &'a mut W);
+
+impl<W: fmt::Write + ?Sized> Sink for FmtSink<'_, W> {
+    type Error = fmt::Error;
+
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let s = core::str::from_utf8(bytes).map_err(|_| fmt::Error)?;
+        self.0.write_str(s)
+    }
+}
+
+/// Adapts an [`io::Write`] into a [`Sink`].
+#[cfg(feature = "std")]
+pub(crate) struct IoSink<'a, W: ?Sized>(pub &'a mut W);
+
+#[cfg(feature = "std")]
+impl<W: io::Write + ?Sized> Sink for IoSink<'_, W> {
+    type Error = io::Error;
+
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write_all(bytes)
+    }
+}
+
+/// Counts the bytes put into it, discarding them.
+pub(crate) struct Counter(pub usize);
+
+impl Sink for Counter {
+    type Error = Infallible;
+
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0 += bytes.len();
+        Ok(())
+    }
+}
+
+/// Writes into a fixed-size slice, failing once it is full.
+///
+/// A failed [`put`][`Sink::put`] writes nothing, but earlier puts stay put.
+pub(crate) struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// The number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl Sink for SliceSink<'_> {
+    type Error = ();
+
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let end = self.len + bytes.len();
+        let dest = self.buf.get_mut(self.len..end).ok_or(())?;
+        dest.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// The error returned when quoting into a slice that is too small to hold the
+/// quoted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    needed: usize,
+    available: usize,
+}
+
+impl BufferTooSmall {
+    pub(crate) fn new(needed: usize, available: usize) -> Self {
+        Self { needed, available }
+    }
+
+    /// The length of the quoted output, i.e. the smallest buffer that would
+    /// have been big enough.
+    pub fn needed(&self) -> usize {
+        self.needed
+    }
+
+    /// The length of the buffer that was given.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer too small: quoting needs {} bytes but only {} are available",
+            self.needed, self.available
+        )
+    }
+}
+
+impl core::error::Error for BufferTooSmall {}
+
+/// Unwrap a result that cannot be an error.
+#[inline]
+pub(crate) fn infallible<T>(result: Result<T, Infallible>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
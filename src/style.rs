@@ -0,0 +1,229 @@
+#![cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+
+//! Quoting in a different style to a dialect's own, e.g. plain single quotes
+//! in Bash, for output that people read.
+
+use core::{fmt, marker::PhantomData};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "bash")]
+use crate::Escaped;
+use crate::{
+    ascii::Char,
+    dialect::{escape_into_sink, needs_quoting, quote_into_sink, Dialect},
+    sink::{FmtSink, Sink},
+    Quotable, Quoting, Render,
+};
+#[cfg(feature = "alloc")]
+use crate::{dialect::join_into_sink, sink::infallible, Quoter};
+
+/// How to quote, for those dialects that can do it in more than one way.
+///
+/// Each dialect's own style is available as a constant, e.g. [`Bash::STYLE`],
+/// so that a style can be built by changing only what matters:
+///
+/// ```
+/// # #[cfg(all(feature = "alloc", feature = "bash"))] {
+/// # use shell_quote::{Bash, Style};
+/// let bash = Bash::with_style(Style { prefer_plain_single_quotes: true, ..Bash::STYLE });
+/// assert_eq!(bash.quote_vec("foo bar"), b"'foo bar'");
+/// assert_eq!(bash.quote_vec("foo\tbar"), b"$'foo\\tbar'");
+/// # }
+/// ```
+///
+/// [`Bash::STYLE`]: `crate::Bash::STYLE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// Use plain single quotes, as `/bin/sh` does, rather than the dialect's
+    /// own quoting – e.g. `$'...'` in Bash – when there are no control
+    /// characters to escape. This has no effect for a dialect whose quotes are
+    /// already plain single quotes.
+    pub prefer_plain_single_quotes: bool,
+    /// Once a word needs any quoting at all, quote the whole word, like
+    /// `'foo bar'`, rather than only the characters that need it, like
+    /// `foo' bar'`. Characters that must be escaped outside of quotes still
+    /// are, and the quotes are opened again after them.
+    pub whole_word: bool,
+}
+
+/// A dialect that quotes in a given [`Style`]. Create one with `with_style`,
+/// e.g. [`Bash::with_style`][`crate::Bash::with_style`].
+///
+/// This implements [`Quoter`], so it can be used wherever the style must be
+/// chosen at runtime.
+#[derive(Debug, Clone, Copy)]
+pub struct Styled<D> {
+    style: Style,
+    dialect: PhantomData<D>,
+}
+
+impl<D> Styled<D> {
+    pub(crate) const fn new(style: Style) -> Self {
+        Self {
+            style,
+            dialect: PhantomData,
+        }
+    }
+
+    /// The style of quoting.
+    pub fn style(&self) -> Style {
+        self.style
+    }
+}
+
+impl<D: sealed::Stylable> Styled<D> {
+    /// Quote a string of bytes into a new `Vec<u8>`.
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec<'a, S: Into<Quotable<'a>>>(&self, s: S) -> Vec<u8> {
+        let mut sout = Vec::new();
+        self.quote_into_vec(s, &mut sout);
+        sout
+    }
+
+    /// Quote a string of bytes into an existing `Vec<u8>`.
+    #[cfg(feature = "alloc")]
+    pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(&self, s: S, sout: &mut Vec<u8>) {
+        infallible(self.quote_into_sink(s.into(), sout))
+    }
+
+    /// Quote many strings of bytes into a new `Vec<u8>`, separated by spaces,
+    /// i.e. as a command line.
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(&self, args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        infallible(join_into_sink(args, &mut sout, |s, sout| {
+            self.quote_into_sink(s, sout)
+        }));
+        sout
+    }
+
+    /// Quote a string into a [`fmt::Write`], like a [`String`] or a
+    /// [`fmt::Formatter`].
+    ///
+    /// [`String`]: alloc::string::String
+    pub fn quote_into_fmt<W: fmt::Write + ?Sized>(&self, s: &str, sout: &mut W) -> fmt::Result {
+        self.quote_into_sink(s.into(), &mut FmtSink(sout))
+    }
+
+    fn quote_into_sink<S: Sink>(&self, s: Quotable, sout: &mut S) -> Result<(), S::Error> {
+        let plain =
+            self.style.prefer_plain_single_quotes && !s.as_bytes().iter().any(u8::is_ascii_control);
+        match (plain, self.style.whole_word) {
+            (true, true) => quote_whole_word::<D::Plain, S>(s, sout),
+            (true, false) => quote_into_sink::<Minimal<D::Plain>, S>(s, sout),
+            (false, true) => quote_whole_word::<D, S>(s, sout),
+            (false, false) => quote_into_sink::<Minimal<D>, S>(s, sout),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: sealed::Stylable> Quoter for Styled<D> {
+    fn quote_into_vec(&self, s: Quotable<'_>, out: &mut Vec<u8>) {
+        Styled::quote_into_vec(self, s, out)
+    }
+}
+
+/// Quote the whole of `s` once it needs any quoting. A string that `D` would
+/// write as it is, like `café` for [`Sh`][`crate::Sh`], is still written as it
+/// is.
+fn quote_whole_word<D, S>(s: Quotable, sout: &mut S) -> Result<(), S::Error>
+where
+    D: Dialect,
+    S: Sink,
+{
+    if !s.as_bytes().is_empty() && needs_quoting::<D>(s) {
+        // From the start, so that any inert prefix is quoted too. Quotes are
+        // opened at the first character that can be inside them, rather than
+        // opened and closed again straight away if that one cannot.
+        escape_into_sink::<WholeWord<D>, S>(s, 0, sout)
+    } else {
+        quote_into_sink::<D, S>(s, sout)
+    }
+}
+
+pub(crate) mod sealed {
+    /// The dialects that can be [`Styled`][`super::Styled`].
+    pub trait Stylable: crate::Dialect {
+        /// The dialect to use for plain single quotes.
+        type Plain: crate::Dialect;
+    }
+
+    /// Plain single quotes, as [`Sh`][`crate::Sh`] uses, for dialects that
+    /// have quotes of their own, like Bash. It is here so that it is available
+    /// even when the `sh` feature is not.
+    #[cfg(feature = "bash")]
+    pub struct SingleQuotes;
+}
+
+// ----------------------------------------------------------------------------
+
+/// `D`, but quoting the whole word: anything that can be inside quotes is.
+struct WholeWord<D>(PhantomData<D>);
+
+impl<D: Dialect> Dialect for WholeWord<D> {
+    const OPEN: &'static [u8] = D::OPEN;
+    const CLOSE: &'static [u8] = D::CLOSE;
+    const EMPTY: &'static [u8] = D::EMPTY;
+    const QUOTE_WHOLE_WORD: bool = D::QUOTE_WHOLE_WORD;
+
+    fn render(ch: Char) -> Render {
+        match D::render(ch) {
+            Render::Literal(Quoting::Either) => Render::Literal(Quoting::Inside),
+            Render::Escape(Quoting::Either, escaped) => Render::Escape(Quoting::Inside, escaped),
+            render => render,
+        }
+    }
+
+    fn first_non_inert(sin: &[u8]) -> Option<usize> {
+        D::first_non_inert(sin)
+    }
+
+    fn literal_run(sin: &[u8]) -> usize {
+        D::literal_run(sin)
+    }
+}
+
+/// `D`, but quoting only the characters that need it.
+struct Minimal<D>(PhantomData<D>);
+
+impl<D: Dialect> Dialect for Minimal<D> {
+    const OPEN: &'static [u8] = D::OPEN;
+    const CLOSE: &'static [u8] = D::CLOSE;
+    const EMPTY: &'static [u8] = D::EMPTY;
+    const QUOTE_WHOLE_WORD: bool = false;
+
+    fn render(ch: Char) -> Render {
+        D::render(ch)
+    }
+
+    fn first_non_inert(sin: &[u8]) -> Option<usize> {
+        D::first_non_inert(sin)
+    }
+
+    fn literal_run(sin: &[u8]) -> usize {
+        D::literal_run(sin)
+    }
+}
+
+#[cfg(feature = "bash")]
+impl Dialect for sealed::SingleQuotes {
+    const OPEN: &'static [u8] = b"'";
+    const CLOSE: &'static [u8] = b"'";
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            Char::PrintableInert(_) | Char::Extended(_) | Char::Utf8(_) => {
+                Render::Literal(Quoting::Either)
+            }
+            Char::SingleQuote => Render::Escape(Quoting::Outside, Escaped::new(b"\\'")),
+            _ => Render::Literal(Quoting::Inside),
+        }
+    }
+}
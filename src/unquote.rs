@@ -0,0 +1,340 @@
+#![cfg(all(
+    feature = "alloc",
+    any(feature = "bash", feature = "fish", feature = "sh")
+))]
+
+//! Errors, and shared helpers, for turning quoted/escaped words back into the
+//! bytes they represent.
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::ascii::Char;
+
+/// An error encountered while unquoting a shell word.
+///
+/// Carries the kind of error and the byte offset into the input at which it
+/// was found. For unterminated quotes, that is the offset of the _opening_
+/// quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnquoteError {
+    kind: UnquoteErrorKind,
+    position: usize,
+}
+
+impl UnquoteError {
+    pub(crate) fn new(kind: UnquoteErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+
+    /// What went wrong.
+    pub fn kind(&self) -> UnquoteErrorKind {
+        self.kind
+    }
+
+    /// The byte offset into the input at which it went wrong.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// The kinds of [`UnquoteError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnquoteErrorKind {
+    /// A single-quoted string was opened but never closed.
+    UnterminatedSingleQuote,
+    /// A double-quoted string was opened but never closed.
+    UnterminatedDoubleQuote,
+    /// An [ANSI-C quoted string][ansi-c-quoting], like `$'foo'`, was opened
+    /// but never closed.
+    ///
+    /// [ansi-c-quoting]:
+    ///     https://www.gnu.org/software/bash/manual/html_node/ANSI_002dC-Quoting.html
+    UnterminatedAnsiCQuote,
+    /// The input ended with a backslash that escapes nothing.
+    TrailingBackslash,
+    /// An escape sequence that is malformed, or that names something that
+    /// cannot be represented, like a Unicode surrogate.
+    InvalidEscape,
+    /// A control operator or redirection, like `|`, `;`, `&&`, or `>`, or a
+    /// newline separating one command from the next. These cannot be part of a
+    /// word, nor of the arguments to a single command.
+    Operator(u8),
+    /// A character that introduces an expansion – parameter, command, tilde,
+    /// brace, history, or pathname expansion – which cannot be performed here.
+    Expansion(u8),
+    /// Any other unquoted shell metacharacter, including whitespace. These may
+    /// mean something to the shell – word splitting, redirection, etc. – so the
+    /// input cannot be understood as a single literal word.
+    Metacharacter(u8),
+}
+
+impl fmt::Display for UnquoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use UnquoteErrorKind::*;
+        match self.kind {
+            UnterminatedSingleQuote => write!(f, "unterminated single quote")?,
+            UnterminatedDoubleQuote => write!(f, "unterminated double quote")?,
+            UnterminatedAnsiCQuote => write!(f, "unterminated ANSI-C quote")?,
+            TrailingBackslash => write!(f, "trailing backslash")?,
+            InvalidEscape => write!(f, "invalid escape sequence")?,
+            Operator(ch) => write!(f, "unsupported operator {:?}", ch as char)?,
+            Expansion(ch) => write!(f, "unsupported expansion {:?}", ch as char)?,
+            Metacharacter(ch) => write!(f, "unsupported metacharacter {:?}", ch as char)?,
+        }
+        write!(f, " at position {}", self.position)
+    }
+}
+
+impl core::error::Error for UnquoteError {}
+
+// ----------------------------------------------------------------------------
+
+/// A position-tracking reader over the bytes of a quoted word.
+pub(crate) struct Cursor<'a> {
+    sin: &'a [u8],
+    pos: usize,
+    /// Where an unquoted `~` would be expanded: at the start of the word, or
+    /// just after an unquoted `=` or `:`.
+    tilde: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(sin: &'a [u8]) -> Self {
+        Self {
+            sin,
+            pos: 0,
+            tilde: 0,
+        }
+    }
+
+    /// Note that a word starts at the next byte.
+    fn start_word(&mut self) {
+        self.tilde = self.pos;
+    }
+
+    /// The next byte and its position.
+    #[inline]
+    pub fn next(&mut self) -> Option<(usize, u8)> {
+        let ch = *self.sin.get(self.pos)?;
+        self.pos += 1;
+        Some((self.pos - 1, ch))
+    }
+
+    /// The next byte and its position, unless it is a blank or a newline,
+    /// i.e. unless it ends an unquoted word.
+    #[inline]
+    pub fn next_in_word(&mut self) -> Option<(usize, u8)> {
+        match self.peek()? {
+            b' ' | b'\t' | b'\n' => None,
+            _ => self.next(),
+        }
+    }
+
+    /// The next byte, without consuming it.
+    #[inline]
+    pub fn peek(&self) -> Option<u8> {
+        self.sin.get(self.pos).copied()
+    }
+
+    /// Consume the next byte if it matches `pred`.
+    #[inline]
+    pub fn next_if(&mut self, pred: impl FnOnce(u8) -> bool) -> Option<u8> {
+        match self.peek() {
+            Some(ch) if pred(ch) => {
+                self.pos += 1;
+                Some(ch)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Check that `ch`, found outside of any quotes at `position` in the input
+/// that `cur` is reading, stands for itself.
+///
+/// This is deliberately strict: everything the quoting side of this crate
+/// would have quoted is rejected, with four exceptions. `%` and `=` are quoted
+/// for reasons to do with _command_ position and assignments – see the notes
+/// in [`crate::ascii`] – but as part of a word they are literal in every shell
+/// we support, and humans write `--foo=bar` unquoted all the time. `#` starts a
+/// comment only at the beginning of a word, and that is dealt with by
+/// [`unquote`] and [`split`]. `~` is expanded only at the beginning of a word,
+/// or after the `=` or a `:` in an assignment; elsewhere, as in `a~b`, it is
+/// literal, and Bash's own `printf %q` leaves it bare. A `=` or `:` that was
+/// itself quoted or escaped does not count.
+pub(crate) fn check_bare(cur: &mut Cursor, ch: u8, position: usize) -> Result<(), UnquoteError> {
+    use Char::*;
+    use UnquoteErrorKind::*;
+    if let b'=' | b':' = ch {
+        cur.tilde = position + 1;
+    }
+    match Char::from_byte(ch) {
+        Printable(b'%' | b'=' | b'#') => Ok(()),
+        Printable(b'~') if position == cur.tilde => Err(UnquoteError::new(Expansion(ch), position)),
+        Printable(b'~') => Ok(()),
+        Printable(b'|' | b'&' | b';' | b'<' | b'>' | b'(' | b')') => {
+            Err(UnquoteError::new(Operator(ch), position))
+        }
+        Printable(b'$' | b'`' | b'*' | b'?' | b'[' | b'{' | b'!') => {
+            Err(UnquoteError::new(Expansion(ch), position))
+        }
+        Printable(_) | HorizontalTab | NewLine => {
+            Err(UnquoteError::new(Metacharacter(ch), position))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Read one word into `sout`, stopping at – but not consuming – an unquoted
+/// blank or newline, or at the end of the input. Each dialect has its own.
+pub(crate) type ReadWord = fn(&mut Cursor, &mut Vec<u8>) -> Result<(), UnquoteError>;
+
+/// Unquote `sin`, which must be a single word, into `sout`. On error, `sout`
+/// is left as it was found.
+pub(crate) fn unquote(
+    sin: &[u8],
+    sout: &mut Vec<u8>,
+    read_word: ReadWord,
+) -> Result<(), UnquoteError> {
+    let len = sout.len();
+    let mut cur = Cursor::new(sin);
+    let result = match cur.peek() {
+        Some(b'#') => Err(UnquoteError::new(UnquoteErrorKind::Metacharacter(b'#'), 0)),
+        _ => read_word(&mut cur, sout).and_then(|()| match cur.next() {
+            Some((pos, ch)) => Err(UnquoteError::new(UnquoteErrorKind::Metacharacter(ch), pos)),
+            None => Ok(()),
+        }),
+    };
+    result.inspect_err(|_| sout.truncate(len))
+}
+
+/// Split `sin` into words, unquoting each one.
+///
+/// Words are separated by unquoted blanks, i.e. spaces and tabs. A `#` at the
+/// start of a word begins a comment that runs to the end of the line. A
+/// newline ends the command; only blanks, comments, and more newlines may
+/// follow.
+pub(crate) fn split(sin: &[u8], read_word: ReadWord) -> Result<Vec<Vec<u8>>, UnquoteError> {
+    let mut words = Vec::new();
+    let mut cur = Cursor::new(sin);
+    let mut newline = None;
+    loop {
+        match cur.peek() {
+            None => break Ok(words),
+            Some(b' ' | b'\t') => {
+                cur.next();
+            }
+            Some(b'\n') => {
+                let (pos, _) = cur.next().unwrap();
+                newline.get_or_insert(pos);
+            }
+            Some(b'#') => while cur.next_if(|ch| ch != b'\n').is_some() {},
+            // Line continuation between words.
+            Some(b'\\') if cur.sin.get(cur.pos + 1) == Some(&b'\n') => {
+                cur.next();
+                cur.next();
+            }
+            Some(_) => match newline {
+                Some(pos) => {
+                    break Err(UnquoteError::new(UnquoteErrorKind::Operator(b'\n'), pos));
+                }
+                None => {
+                    let mut word = Vec::new();
+                    cur.start_word();
+                    read_word(&mut cur, &mut word)?;
+                    words.push(word);
+                }
+            },
+        }
+    }
+}
+
+/// Unquote the remainder of a single-quoted string, the opening quote of which
+/// was at `start`. There are no escapes in POSIX single quotes.
+#[cfg(any(feature = "bash", feature = "sh"))]
+pub(crate) fn single_quoted(
+    cur: &mut Cursor,
+    start: usize,
+    sout: &mut Vec<u8>,
+) -> Result<(), UnquoteError> {
+    loop {
+        match cur.next() {
+            Some((_, b'\'')) => break Ok(()),
+            Some((_, ch)) => sout.push(ch),
+            None => {
+                break Err(UnquoteError::new(
+                    UnquoteErrorKind::UnterminatedSingleQuote,
+                    start,
+                ))
+            }
+        }
+    }
+}
+
+/// Unquote the remainder of a double-quoted string, the opening quote of which
+/// was at `start`, following POSIX rules.
+#[cfg(any(feature = "bash", feature = "sh"))]
+pub(crate) fn double_quoted(
+    cur: &mut Cursor,
+    start: usize,
+    sout: &mut Vec<u8>,
+) -> Result<(), UnquoteError> {
+    use UnquoteErrorKind::*;
+    loop {
+        match cur.next() {
+            Some((_, b'"')) => break Ok(()),
+            // Inside double quotes, backslash escapes only these; see the
+            // extract from dash(1) in the documentation for `Sh`.
+            Some((_, b'\\')) => match cur.next() {
+                Some((_, ch @ (b'$' | b'`' | b'"' | b'\\'))) => sout.push(ch),
+                Some((_, b'\n')) => (), // Line continuation.
+                Some((_, ch)) => sout.extend([b'\\', ch]),
+                None => break Err(UnquoteError::new(UnterminatedDoubleQuote, start)),
+            },
+            Some((pos, ch @ (b'$' | b'`'))) => break Err(UnquoteError::new(Expansion(ch), pos)),
+            Some((_, ch)) => sout.push(ch),
+            None => break Err(UnquoteError::new(UnterminatedDoubleQuote, start)),
+        }
+    }
+}
+
+/// Unquote the byte following an unquoted backslash at `start`.
+#[cfg(any(feature = "bash", feature = "sh"))]
+pub(crate) fn backslashed(
+    cur: &mut Cursor,
+    start: usize,
+    sout: &mut Vec<u8>,
+) -> Result<(), UnquoteError> {
+    match cur.next() {
+        Some((_, b'\n')) => Ok(()), // Line continuation.
+        Some((_, ch)) => {
+            sout.push(ch);
+            Ok(())
+        }
+        None => Err(UnquoteError::new(
+            UnquoteErrorKind::TrailingBackslash,
+            start,
+        )),
+    }
+}
+
+/// Consume up to `max` digits in `radix`, accumulating them onto `acc`.
+/// Returns the accumulated value and the number of digits consumed.
+#[cfg(any(feature = "bash", feature = "fish"))]
+pub(crate) fn digits(cur: &mut Cursor, radix: u32, max: usize, mut acc: u32) -> (u32, usize) {
+    let mut count = 0;
+    while count < max {
+        match cur.peek().and_then(|ch| (ch as char).to_digit(radix)) {
+            Some(digit) => {
+                cur.next();
+                acc = acc * radix + digit;
+                count += 1;
+            }
+            None => break,
+        }
+    }
+    (acc, count)
+}
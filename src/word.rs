@@ -0,0 +1,357 @@
+#![cfg(feature = "alloc")]
+
+//! An intermediate representation of quoted words, between deciding how to
+//! quote a string and writing out the quoted form for a particular shell.
+
+use alloc::vec::Vec;
+
+use crate::Quotable;
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+use crate::{
+    dialect::{chars, prepare, Dialect, Prepared},
+    Quoting, Render,
+};
+
+/// A piece of a [`ShellWord`].
+///
+/// The contents of each segment are the bytes – or text – that the shell
+/// should see once it has removed the quotes. How they are written, e.g. which
+/// characters are escaped, is up to the [`Renderer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Written as it is, without quotes. The contents must need no quoting in
+    /// the target shell.
+    Bare(Quotable<'a>),
+    /// In single quotes, like `'foo bar'`.
+    SingleQuoted(Quotable<'a>),
+    /// In double quotes, like `"foo bar"`.
+    DoubleQuoted(Quotable<'a>),
+    /// In [ANSI-C quotes][ansi-c-quoting], like `$'foo\tbar'`.
+    ///
+    /// [ansi-c-quoting]:
+    ///     https://www.gnu.org/software/bash/manual/html_node/ANSI_002dC-Quoting.html
+    AnsiC(Quotable<'a>),
+    /// A single byte, escaped on its own, outside of quotes, like `\'`.
+    Escape(u8),
+}
+
+/// A quoted word: a sequence of [`Segment`]s which, written out one after the
+/// other by a [`Renderer`], form a single word in the shell.
+///
+/// Plan a word with, for example, [`Bash::plan`][`crate::Bash::plan`], then
+/// inspect it, change it, compare it with another plan, and render it, for
+/// Bash or for another shell. Rendering a word planned for the same shell
+/// gives the same output as quoting directly.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "fish", feature = "sh"))] {
+/// # use shell_quote::{Fish, Quotable, Segment, Sh, ShellWord};
+/// let word = Sh::plan("it's here");
+/// assert_eq!(
+///     word.segments(),
+///     [
+///         Segment::Bare(Quotable::Text("it")),
+///         Segment::Escape(b'\''),
+///         Segment::Bare(Quotable::Text("s")),
+///         Segment::SingleQuoted(Quotable::Text(" here")),
+///     ]
+/// );
+/// assert_eq!(word.render::<Sh>(), Sh::quote_vec("it's here"));
+/// assert_eq!(word.render::<Fish>(), b"it\\'s' here'");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellWord<'a> {
+    segments: Vec<Segment<'a>>,
+}
+
+impl<'a> ShellWord<'a> {
+    /// A word made of the given segments.
+    ///
+    /// No segments at all make an empty word, which renders as nothing, i.e.
+    /// not as a word at all. To render an empty string, use an empty quoted
+    /// segment, like `Segment::SingleQuoted(Quotable::Text(""))`.
+    pub fn new(segments: Vec<Segment<'a>>) -> Self {
+        Self { segments }
+    }
+
+    /// The segments of this word.
+    pub fn segments(&self) -> &[Segment<'a>] {
+        &self.segments
+    }
+
+    /// The segments of this word, to change them.
+    pub fn segments_mut(&mut self) -> &mut Vec<Segment<'a>> {
+        &mut self.segments
+    }
+
+    /// The segments of this word, taking ownership of them.
+    pub fn into_segments(self) -> Vec<Segment<'a>> {
+        self.segments
+    }
+
+    /// Render this word for the shell `R` into a new `Vec<u8>`.
+    pub fn render<R: Renderer>(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.render_into::<R>(&mut out);
+        out
+    }
+
+    /// Render this word for the shell `R` into an existing `Vec<u8>`.
+    pub fn render_into<R: Renderer>(&self, out: &mut Vec<u8>) {
+        for segment in &self.segments {
+            R::render_segment(segment, out);
+        }
+    }
+}
+
+/// Writing out [`Segment`]s for a particular shell.
+///
+/// [`Sh`][`crate::Sh`], [`Bash`][`crate::Bash`], and [`Fish`][`crate::Fish`]
+/// implement this. A renderer must write every kind of segment, even those
+/// that the shell has no direct syntax for; [`Sh`][`crate::Sh`], for example,
+/// renders [`Segment::AnsiC`] by quoting the contents as it would any other
+/// string.
+pub trait Renderer {
+    /// Render `segment` into `out`.
+    fn render_segment(segment: &Segment<'_>, out: &mut Vec<u8>);
+}
+
+// ----------------------------------------------------------------------------
+
+/// Plan how `D` quotes `s`. Runs of characters that `D` writes inside quotes
+/// become `quoted` segments.
+///
+/// This follows the same decisions as quoting does, so that rendering the plan
+/// with `D` gives the same output.
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub(crate) fn plan<'a, D: Dialect + ?Sized>(
+    s: Quotable<'a>,
+    quoted: fn(Quotable<'a>) -> Segment<'a>,
+) -> ShellWord<'a> {
+    let start = match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => return ShellWord::new(alloc::vec![Segment::SingleQuoted(s)]),
+        Prepared::Inert => return ShellWord::new(alloc::vec![Segment::Bare(s)]),
+        Prepared::Escape(start) => start,
+    };
+    let mut segments = Vec::new();
+    let mut inside_quotes = D::QUOTE_WHOLE_WORD;
+    let mut run = 0;
+    let flush = |segments: &mut Vec<_>, inside_quotes, run, end| {
+        if run < end {
+            let contents = slice(s, run, end);
+            segments.push(if inside_quotes {
+                quoted(contents)
+            } else {
+                Segment::Bare(contents)
+            });
+        }
+    };
+    let bytes = s.as_bytes();
+    for (index, ch) in chars(s, start) {
+        let len = match ch {
+            crate::Char::Utf8(ch) => ch.len_utf8(),
+            _ => 1,
+        };
+        let (quoting, escaped) = match D::render(ch) {
+            Render::Literal(Quoting::Either) => continue,
+            Render::Literal(quoting) => (quoting, false),
+            // Inside quotes, an escape is part of the quoted run; it is up to
+            // the renderer to write it.
+            Render::Escape(Quoting::Either, _) if inside_quotes => continue,
+            Render::Escape(quoting, _) => (quoting, true),
+        };
+        if let (true, Quoting::Outside) | (false, Quoting::Inside) = (inside_quotes, quoting) {
+            flush(&mut segments, inside_quotes, run, index);
+            inside_quotes = !inside_quotes;
+            run = index;
+        }
+        if escaped && !inside_quotes {
+            flush(&mut segments, inside_quotes, run, index);
+            segments.extend(
+                bytes[index..index + len]
+                    .iter()
+                    .copied()
+                    .map(Segment::Escape),
+            );
+            run = index + len;
+        }
+    }
+    flush(&mut segments, inside_quotes, run, bytes.len());
+    ShellWord::new(segments)
+}
+
+/// A kind of [`Segment`], for [`plan_shortest`].
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Bare,
+    SingleQuoted,
+    DoubleQuoted,
+    #[cfg_attr(not(feature = "bash"), allow(dead_code))]
+    AnsiC,
+    Escape,
+}
+
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+impl Kind {
+    fn segment(self, contents: Quotable) -> Segment {
+        match self {
+            Kind::Bare => Segment::Bare(contents),
+            Kind::SingleQuoted => Segment::SingleQuoted(contents),
+            Kind::DoubleQuoted => Segment::DoubleQuoted(contents),
+            Kind::AnsiC => Segment::AnsiC(contents),
+            Kind::Escape => Segment::Escape(contents.as_bytes()[0]),
+        }
+    }
+}
+
+/// Plan the shortest way for `D` to quote `s`, from segments of the given
+/// `kinds`, where `D` renders each of those kinds as quotes around the
+/// characters in them, each written on its own.
+///
+/// This finds, character by character, the cheapest way to have written
+/// everything up to and including that character, ending in a segment of
+/// each kind. Costs are measured by rendering, so they are exact. Where there
+/// is a tie, the plan with fewer segments wins, then the kind that comes first
+/// in `kinds`.
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub(crate) fn plan_shortest<'a, D>(s: Quotable<'a>, kinds: &[Kind]) -> ShellWord<'a>
+where
+    D: Dialect + Renderer + ?Sized,
+{
+    match prepare::<D>(s.as_bytes()) {
+        Prepared::Empty => return ShellWord::new(alloc::vec![Segment::SingleQuoted(s)]),
+        Prepared::Inert => return ShellWord::new(alloc::vec![Segment::Bare(s)]),
+        Prepared::Escape(_) => (),
+    }
+    let mut scratch = Vec::new();
+    let mut render = |segment: Segment| {
+        scratch.clear();
+        D::render_segment(&segment, &mut scratch);
+        scratch.len()
+    };
+    // The cost of opening, and closing, each kind of segment.
+    let overhead: Vec<usize> = kinds
+        .iter()
+        .map(|kind| match kind {
+            Kind::Escape => 0,
+            kind => render(kind.segment(slice(s, 0, 0))),
+        })
+        .collect();
+    // For each character, its offset, and the kind of segment that the
+    // cheapest plan ending in each kind had before it.
+    let mut steps: Vec<(usize, Vec<Option<usize>>)> = Vec::new();
+    let mut costs: Vec<Option<(usize, usize)>> = alloc::vec![None; kinds.len()];
+    let bytes = s.as_bytes();
+    for (index, ch) in chars(s, 0) {
+        let len = match ch {
+            crate::Char::Utf8(ch) => ch.len_utf8(),
+            _ => 1,
+        };
+        let contents = slice(s, index, index + len);
+        let mut next = alloc::vec![None; kinds.len()];
+        let mut from = alloc::vec![None; kinds.len()];
+        for (k, &kind) in kinds.iter().enumerate() {
+            let cost = match kind {
+                Kind::Bare if D::render(ch) != Render::Literal(Quoting::Either) => continue,
+                Kind::Escape if len != 1 => continue,
+                // In an interactive Bash, `!` may begin history expansion in
+                // double quotes, and it cannot be escaped there. Bash renders
+                // it outside of them, so it does not cost what is measured.
+                Kind::DoubleQuoted if bytes[index] == b'!' => continue,
+                Kind::Escape => render(kind.segment(contents)),
+                kind => render(kind.segment(contents)) - overhead[k],
+            };
+            let best = if steps.is_empty() {
+                Some(((overhead[k], 1), None))
+            } else {
+                costs
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(j, total)| {
+                        let (bytes, segments) = (*total)?;
+                        Some(if j == k && kind != Kind::Escape {
+                            ((bytes, segments), Some(j))
+                        } else {
+                            ((bytes + overhead[k], segments + 1), Some(j))
+                        })
+                    })
+                    .min_by_key(|&(total, _)| total)
+            };
+            if let Some(((bytes, segments), j)) = best {
+                next[k] = Some((bytes + cost, segments));
+                from[k] = j;
+            }
+        }
+        costs = next;
+        steps.push((index, from));
+    }
+    // Walk back from the cheapest plan to find the kind of each character.
+    let mut k = costs
+        .iter()
+        .enumerate()
+        .filter_map(|(k, cost)| cost.map(|cost| (cost, k)))
+        .min()
+        .map(|(_, k)| k);
+    let mut plan = Vec::with_capacity(steps.len());
+    for (index, from) in steps.iter().rev() {
+        let kind = k.expect("every character can be written in some kind of segment");
+        plan.push((*index, kind));
+        k = from[kind];
+    }
+    plan.reverse();
+    // Then gather runs of characters of the same kind into segments.
+    let mut segments = Vec::new();
+    let mut run = 0;
+    for (i, &(_, k)) in plan.iter().enumerate() {
+        let next = plan.get(i + 1);
+        if kinds[k] == Kind::Escape || next.map(|&(_, next)| next) != Some(k) {
+            let end = next.map_or(bytes.len(), |&(index, _)| index);
+            segments.push(kinds[k].segment(slice(s, run, end)));
+            run = end;
+        }
+    }
+    ShellWord::new(segments)
+}
+
+/// The bytes, or text, of `s` between `start` and `end`, which must be at
+/// character boundaries.
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+fn slice(s: Quotable, start: usize, end: usize) -> Quotable {
+    match s {
+        Quotable::Bytes(bytes) => Quotable::Bytes(&bytes[start..end]),
+        Quotable::Text(text) => Quotable::Text(&text[start..end]),
+    }
+}
+
+/// Write `contents` in single quotes, closing them around each single quote,
+/// escaping it, and opening them again, as in `'it'\''s'`. This works in
+/// `/bin/sh` and its descendants.
+#[cfg(any(feature = "bash", feature = "sh"))]
+pub(crate) fn single_quoted(contents: &[u8], out: &mut Vec<u8>) {
+    out.push(b'\'');
+    for &ch in contents {
+        match ch {
+            b'\'' => out.extend_from_slice(b"'\\''"),
+            ch => out.push(ch),
+        }
+    }
+    out.push(b'\'');
+}
+
+/// Write `contents` in double quotes, escaping `special` characters with a
+/// backslash.
+#[cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+pub(crate) fn double_quoted(contents: &[u8], special: &[u8], out: &mut Vec<u8>) {
+    out.push(b'"');
+    for &ch in contents {
+        if special.contains(&ch) {
+            out.push(b'\\');
+        }
+        out.push(ch);
+    }
+    out.push(b'"');
+}
@@ -0,0 +1,674 @@
+#![cfg(feature = "bash")]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "alloc")]
+use crate::explain::{explain, Explanation};
+#[cfg(feature = "std")]
+use crate::sink::IoSink;
+#[cfg(feature = "alloc")]
+use crate::word::{self, Kind, Renderer, Segment, ShellWord};
+use crate::{
+    ascii::Char,
+    dialect::{
+        needs_quoting, quote_into_sink, quote_into_slice, quoted_len, Dialect, Escaped, Quoting,
+        Render,
+    },
+    sink::{BufferTooSmall, FmtSink},
+    style::{
+        sealed::{SingleQuotes, Stylable},
+        Style, Styled,
+    },
+    Bash, Quotable, Quoted,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    dialect::{escape_into_sink, join_into_sink, quote_cow},
+    sink::infallible,
+    JoinInto, QuoteInto, UnquoteError,
+};
+
+/// Quote byte strings for use with Z Shell.
+///
+/// Like [`Bash`], this quotes with [ANSI-C quoting][zsh-quoting], like
+/// `$'foo\tbar'`, and every escape sequence it writes means the same in both
+/// shells. The rules for `NUL`, `%`, `=`, and `~` are Z Shell's own, though,
+/// as below, and it is tested against `zsh`. For Z Shell's own ways of
+/// quoting, see [`Zsh::quote_flag`].
+///
+/// # `NUL`
+///
+/// Unlike Bash, Z Shell handles NUL in its strings, so a `NUL` byte, written
+/// as `\x00`, comes through faithfully:
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use shell_quote::Zsh;
+/// assert_eq!(Zsh::quote_vec(b"foo\0bar"), b"$'foo\\x00bar'");
+/// # }
+/// ```
+///
+/// The string can be assigned to a variable, or passed to a builtin, like
+/// `print` or `printf`, or to a function. It still cannot be passed as an
+/// argument to an external command, since those are C strings, terminated by
+/// NUL.
+///
+/// # `%`, `=`, and expansions
+///
+/// Z Shell tests the _literal_ token for a [job specification][job-control],
+/// so quoting `%` – which this crate always does – is enough to stop a word
+/// like `%1` being read as one, even in command position. Bash differs; see
+/// the warning on [`Bash`].
+///
+/// A word beginning with `=` is subject to [`EQUALS`][zsh-options]
+/// expansion, e.g. `=ls` becomes `/bin/ls`, and with `MAGIC_EQUAL_SUBST`, a
+/// `~` after a `=` anywhere in an argument is expanded. With `EXTENDED_GLOB`,
+/// `^`, `#`, and `~` are special in patterns. None of these characters are
+/// inert, so all are quoted, whatever options are set.
+///
+/// # Notes
+///
+/// From zshmisc(1):
+///
+///   A string enclosed between `$'` and `'` is processed the same way as the
+///   string arguments of the print builtin, and the resulting string is
+///   considered to be entirely quoted.
+///
+/// Only these escape sequences are used, all of which Bash understands too:
+///
+///   ```text
+///   \a     bell character
+///   \b     backspace
+///   \e, \E escape
+///   \f     form feed
+///   \n     linefeed (newline)
+///   \r     carriage return
+///   \t     horizontal tab
+///   \v     vertical tab
+///   \\     backslash
+///   \'     single quote
+///   \xNN   character code in hexadecimal
+///   ```
+///
+/// Characters of U+0080 and above, when quoting text, are written as they are
+/// rather than with `\u`, which Z Shell encodes according to the locale.
+///
+/// [zsh-quoting]: https://zsh.sourceforge.io/Doc/Release/Shell-Grammar.html#Quoting
+/// [zsh-options]: https://zsh.sourceforge.io/Doc/Release/Options.html
+/// [job-control]: https://zsh.sourceforge.io/Doc/Release/Jobs-_0026-Signals.html
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Zsh;
+
+/// A form of Z Shell's `q` flag for parameter expansion, like `${(q)var}`,
+/// for use with [`Zsh::quote_flag`].
+///
+/// The output of each is the same as Z Shell's in the `C` locale, with its
+/// default options, and when not interactive. In other locales, Z Shell may
+/// write printable non-ASCII characters as they are. See [`zshexpn(1)`] for
+/// more about each.
+///
+/// [`zshexpn(1)`]: https://zsh.sourceforge.io/Doc/Release/Expansion.html#Parameter-Expansion-Flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZshFlag {
+    /// `(q)`: special characters are escaped with a backslash, and those that
+    /// are not printable are written in `$'...'`, one at a time. An empty
+    /// string is written as nothing at all.
+    Q,
+    /// `(qq)`: single quotes, like `'foo bar'`.
+    Qq,
+    /// `(qqq)`: double quotes, like `"foo bar"`.
+    Qqq,
+    /// `(qqqq)`: ANSI-C quotes, like `$'foo bar'`.
+    Qqqq,
+    /// `(q-)`: single quotes, but only if something needs them, like
+    /// `'foo bar'`. A `'` is escaped with a backslash instead, as in
+    /// `it\''s here'`.
+    QMinus,
+    /// `(q+)`: as `(q-)`, but with `$'...'` for the whole string when it has
+    /// a character that is not printable, as `typeset` shows values.
+    QPlus,
+}
+
+impl ZshFlag {
+    /// The flag as it is written, e.g. `qq` or `q-`.
+    pub fn flag(self) -> &'static str {
+        match self {
+            ZshFlag::Q => "q",
+            ZshFlag::Qq => "qq",
+            ZshFlag::Qqq => "qqq",
+            ZshFlag::Qqqq => "qqqq",
+            ZshFlag::QMinus => "q-",
+            ZshFlag::QPlus => "q+",
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Bash's rules, but for `NUL`, `=`, `~`, and `%`; see the notes on [`Zsh`].
+impl Dialect for Zsh {
+    const OPEN: &'static [u8] = Bash::OPEN;
+    const CLOSE: &'static [u8] = Bash::CLOSE;
+    const QUOTE_WHOLE_WORD: bool = Bash::QUOTE_WHOLE_WORD;
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            // Z Shell keeps `NUL` in its strings. It is written as `\x00`
+            // rather than `\0`, since an octal escape would run on into any
+            // digits after it.
+            Char::Control(0) => Render::Escape(Quoting::Inside, Escaped::new(b"\\x00")),
+            // `=` and `~` are expanded at the start of a word, `~` after any
+            // `=` too with `MAGIC_EQUAL_SUBST`, and `%` starts a job
+            // specification. Where a character is in its word is not known
+            // here, so these are quoted wherever they are.
+            Char::Printable(b'=' | b'~' | b'%') => Render::Literal(Quoting::Inside),
+            ch => Bash::render(ch),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl QuoteInto<String> for Zsh {
+    fn quote_into<'q, S: Into<Quotable<'q>>>(s: S, out: &mut String) {
+        Self::quote_into_vec(s, unsafe { out.as_mut_vec() })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl JoinInto<String> for Zsh {
+    fn join_into<'q, I>(args: I, out: &mut String)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'q>>,
+    {
+        Self::join_into_vec(args, unsafe { out.as_mut_vec() })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Renderer for Zsh {
+    fn render_segment(segment: &Segment<'_>, out: &mut Vec<u8>) {
+        match *segment {
+            // Escape sequences follow Z Shell's rules, `NUL` included.
+            Segment::AnsiC(s) => infallible(escape_into_sink::<Self, _>(s, 0, out)),
+            Segment::Escape(byte) if !matches!(byte, b' '..=b'~') => {
+                infallible(quote_into_sink::<Self, _>(Quotable::Bytes(&[byte]), out))
+            }
+            _ => Bash::render_segment(segment, out),
+        }
+    }
+}
+
+impl Stylable for Zsh {
+    type Plain = SingleQuotes;
+}
+
+// ----------------------------------------------------------------------------
+
+/// Each of these is as the function of the same name on [`Bash`], but with Z
+/// Shell's rules.
+impl Zsh {
+    /// How [`Zsh`] quotes: the whole word, in ANSI-C quotes.
+    pub const STYLE: Style = Bash::STYLE;
+
+    /// Quote in a different [`Style`], e.g. with plain single quotes where
+    /// possible, or quoting only the characters that need it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use shell_quote::{Zsh, Style};
+    /// let zsh = Zsh::with_style(Style { prefer_plain_single_quotes: true, ..Zsh::STYLE });
+    /// assert_eq!(zsh.quote_vec("foo bar"), b"'foo bar'");
+    /// assert_eq!(zsh.quote_vec("foo\tbar"), b"$'foo\\tbar'");
+    /// let zsh = Zsh::with_style(Style { whole_word: false, ..Zsh::STYLE });
+    /// assert_eq!(zsh.quote_vec("foo bar"), b"foo$' bar'");
+    /// # }
+    /// ```
+    ///
+    pub const fn with_style(style: Style) -> Styled<Self> {
+        Styled::new(style)
+    }
+
+    /// See [`Bash::quote_vec`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Zsh;
+    /// assert_eq!(Zsh::quote_vec("foobar"), b"foobar");
+    /// assert_eq!(Zsh::quote_vec("foo bar"), b"$'foo bar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        let mut sout = Vec::new();
+        Self::quote_into_vec(s, &mut sout);
+        sout
+    }
+
+    /// See [`Bash::quote_into_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn quote_into_vec<'a, S: Into<Quotable<'a>>>(s: S, sout: &mut Vec<u8>) {
+        infallible(quote_into_sink::<Self, _>(s.into(), sout))
+    }
+
+    /// See [`Bash::quote_cow`].
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, [u8]> {
+        quote_cow::<Self>(s.into())
+    }
+
+    /// See [`Bash::quote_cow_str`].
+    #[cfg(feature = "alloc")]
+    pub fn quote_cow_str<'a, S: Into<Quotable<'a>>>(s: S) -> Cow<'a, str> {
+        // SAFETY: As for Bash, text is quoted as text, and when quoting bytes,
+        // everything outside of ASCII is escaped.
+        match Self::quote_cow(s) {
+            Cow::Borrowed(bytes) => Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) }),
+            Cow::Owned(bytes) => Cow::Owned(unsafe { String::from_utf8_unchecked(bytes) }),
+        }
+    }
+
+    /// See [`Bash::needs_quoting`].
+    pub fn needs_quoting<'a, S: Into<Quotable<'a>>>(s: S) -> bool {
+        needs_quoting::<Self>(s.into())
+    }
+
+    /// See [`Bash::explain`].
+    #[cfg(feature = "alloc")]
+    pub fn explain<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<Explanation> {
+        explain::<Self>(s.into())
+    }
+
+    /// See [`Bash::plan`].
+    #[cfg(feature = "alloc")]
+    pub fn plan<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan::<Self>(s.into(), Segment::AnsiC)
+    }
+
+    /// See [`Bash::plan_shortest`].
+    #[cfg(feature = "alloc")]
+    pub fn plan_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> ShellWord<'a> {
+        word::plan_shortest::<Self>(
+            s.into(),
+            &[
+                Kind::Bare,
+                Kind::SingleQuoted,
+                Kind::DoubleQuoted,
+                Kind::AnsiC,
+                Kind::Escape,
+            ],
+        )
+    }
+
+    /// See [`Bash::quote_vec_shortest`].
+    #[cfg(feature = "alloc")]
+    pub fn quote_vec_shortest<'a, S: Into<Quotable<'a>>>(s: S) -> Vec<u8> {
+        Self::plan_shortest(s).render::<Self>()
+    }
+
+    /// Quote a string of bytes exactly as Z Shell's `q` flag does, in one of
+    /// its forms; see [`ZshFlag`].
+    ///
+    /// `NUL` is quoted too, since Z Shell can hold it in a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::{Zsh, ZshFlag};
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::Q, "it's here"), b"it\\'s\\ here");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::Qq, "it's here"), b"'it'\\''s here'");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::Qqq, "it's $HOME"), b"\"it's \\$HOME\"");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::Qqqq, "it's\n"), b"$'it\\'s\\n'");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::QMinus, "foo bar"), b"'foo bar'");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::QMinus, "foo/bar"), b"foo/bar");
+    /// assert_eq!(Zsh::quote_flag(ZshFlag::QPlus, "foo\tbar"), b"$'foo\\tbar'");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn quote_flag<'a, S: Into<Quotable<'a>>>(flag: ZshFlag, s: S) -> Vec<u8> {
+        let s = s.into();
+        let mut sout = Vec::new();
+        match flag {
+            ZshFlag::Q => flags::backslashes(s.as_bytes(), &mut sout),
+            ZshFlag::Qq => flags::single_quotes(s.as_bytes(), &mut sout),
+            ZshFlag::Qqq => flags::double_quotes(s.as_bytes(), &mut sout),
+            ZshFlag::Qqqq => flags::dollar_quotes(s.as_bytes(), &mut sout),
+            ZshFlag::QMinus => flags::single_quotes_optional(s.as_bytes(), &mut sout),
+            ZshFlag::QPlus => flags::quoted_zputs(s.as_bytes(), &mut sout),
+        }
+        sout
+    }
+
+    /// See [`Bash::quoted_len`].
+    pub fn quoted_len<'a, S: Into<Quotable<'a>>>(s: S) -> usize {
+        quoted_len::<Self>(s.into())
+    }
+
+    /// See [`Bash::quote_into_slice`].
+    pub fn quote_into_slice<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut [u8],
+    ) -> Result<usize, BufferTooSmall> {
+        quote_into_slice::<Self>(s.into(), sout)
+    }
+
+    /// See [`Bash::quote_into_fmt`].
+    pub fn quote_into_fmt<'a, S, W>(s: S, sout: &mut W) -> fmt::Result
+    where
+        S: Into<Quotable<'a>>,
+        W: fmt::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut FmtSink(sout))
+    }
+
+    /// See [`Bash::display`].
+    pub fn display<'a, S: Into<Quotable<'a>>>(s: S) -> Quoted<'a, Self> {
+        Quoted::new(s.into())
+    }
+
+    /// See [`Bash::join_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn join_vec<'a, I>(args: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        let mut sout = Vec::new();
+        Self::join_into_vec(args, &mut sout);
+        sout
+    }
+
+    /// See [`Bash::join_into_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn join_into_vec<'a, I>(args: I, sout: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+    {
+        infallible(join_into_sink(args, sout, quote_into_sink::<Self, _>))
+    }
+
+    /// See [`Bash::quote_into_writer`].
+    #[cfg(feature = "std")]
+    pub fn quote_into_writer<'a, S, W>(s: S, sout: &mut W) -> io::Result<()>
+    where
+        S: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        quote_into_sink::<Self, _>(s.into(), &mut IoSink(sout))
+    }
+
+    /// See [`Bash::join_into_writer`].
+    #[cfg(feature = "std")]
+    pub fn join_into_writer<'a, I, W>(args: I, sout: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<Quotable<'a>>,
+        W: io::Write + ?Sized,
+    {
+        join_into_sink(args, &mut IoSink(sout), quote_into_sink::<Self, _>)
+    }
+
+    /// See [`Bash::unquote`]. `\0` and `\x00` decode to NUL, which Z Shell,
+    /// unlike Bash, keeps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use shell_quote::Zsh;
+    /// assert_eq!(Zsh::unquote("$'foo\\x00bar'").unwrap(), b"foo\0bar");
+    /// ```
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn unquote<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<u8>, UnquoteError> {
+        Bash::unquote(s)
+    }
+
+    /// See [`Bash::unquote_into_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn unquote_into_vec<'a, S: Into<Quotable<'a>>>(
+        s: S,
+        sout: &mut Vec<u8>,
+    ) -> Result<(), UnquoteError> {
+        Bash::unquote_into_vec(s, sout)
+    }
+
+    /// See [`Bash::split`].
+    #[cfg(feature = "alloc")]
+    pub fn split<'a, S: Into<Quotable<'a>>>(s: S) -> Result<Vec<Vec<u8>>, UnquoteError> {
+        Bash::split(s)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Quoting as Z Shell's `q` flags do it, after `quotestring` and
+/// `quotedzputs` in `Src/utils.c` in Z Shell's source, as they behave in the
+/// `C` locale, with default options, when not interactive.
+#[cfg(feature = "alloc")]
+mod flags {
+    use alloc::vec::Vec;
+
+    /// Z Shell's `ispecial`. `!` is special only in an interactive shell.
+    fn is_special(ch: u8) -> bool {
+        matches!(
+            ch,
+            b'#' | b'$'
+                | b'^'
+                | b'*'
+                | b'('
+                | b')'
+                | b'='
+                | b'|'
+                | b'{'
+                | b'}'
+                | b'['
+                | b']'
+                | b'`'
+                | b'<'
+                | b'>'
+                | b'?'
+                | b'~'
+                | b';'
+                | b'&'
+                | b'\n'
+                | b'\t'
+                | b' '
+                | b'\\'
+                | b'\''
+                | b'"'
+        )
+    }
+
+    /// Must `sin[index]` be quoted? `=` and `~` are special only at the start
+    /// of a word, without `MAGIC_EQUAL_SUBST` and `EXTENDED_GLOB`.
+    fn is_special_at(sin: &[u8], index: usize) -> bool {
+        match sin[index] {
+            b'=' | b'~' => index == 0,
+            ch => is_special(ch),
+        }
+    }
+
+    fn is_printable(ch: u8) -> bool {
+        matches!(ch, b' '..=b'~')
+    }
+
+    /// `(q)`.
+    pub fn backslashes(sin: &[u8], sout: &mut Vec<u8>) {
+        for (index, &ch) in sin.iter().enumerate() {
+            let special = is_special_at(sin, index);
+            if special && ch == b'\n' {
+                sout.extend(b"$'\\n'");
+            } else if is_printable(ch) {
+                if special {
+                    sout.push(b'\\');
+                }
+                sout.push(ch);
+            } else {
+                sout.extend(b"$'");
+                unprintable(ch, sin.get(index + 1), sout);
+                sout.push(b'\'');
+            }
+        }
+    }
+
+    /// `(qq)`.
+    pub fn single_quotes(sin: &[u8], sout: &mut Vec<u8>) {
+        sout.push(b'\'');
+        for &ch in sin {
+            match ch {
+                b'\'' => sout.extend(b"'\\''"),
+                ch => sout.push(ch),
+            }
+        }
+        sout.push(b'\'');
+    }
+
+    /// `(qqq)`.
+    pub fn double_quotes(sin: &[u8], sout: &mut Vec<u8>) {
+        sout.push(b'"');
+        for (index, &ch) in sin.iter().enumerate() {
+            match ch {
+                // `$'` means nothing in double quotes, so is left as it is.
+                b'$' if sin.get(index + 1) == Some(&b'\'') => (),
+                b'$' | b'`' | b'"' | b'\\' => sout.push(b'\\'),
+                _ => (),
+            }
+            sout.push(ch);
+        }
+        sout.push(b'"');
+    }
+
+    /// `(qqqq)`.
+    pub fn dollar_quotes(sin: &[u8], sout: &mut Vec<u8>) {
+        sout.extend(b"$'");
+        for (index, &ch) in sin.iter().enumerate() {
+            match ch {
+                // `!` because `BANG_HIST` is set, even when not interactive.
+                b'\\' | b'\'' | b'!' => sout.extend([b'\\', ch]),
+                ch if is_printable(ch) => sout.push(ch),
+                ch => unprintable(ch, sin.get(index + 1), sout),
+            }
+        }
+        sout.push(b'\'');
+    }
+
+    /// `(q-)`. Quotes are opened at `start` once a special character is
+    /// found, so that what comes before it – back to the start of the string,
+    /// or to the last escaped single quote – is inside them too.
+    pub fn single_quotes_optional(sin: &[u8], sout: &mut Vec<u8>) {
+        if sin.is_empty() {
+            sout.extend(b"''");
+            return;
+        }
+        let mut start = sout.len();
+        let mut quoting = false;
+        for (index, &ch) in sin.iter().enumerate() {
+            if !is_special_at(sin, index) {
+                sout.push(ch);
+            } else if ch == b'\'' {
+                if quoting {
+                    sout.push(b'\'');
+                    quoting = false;
+                }
+                sout.extend(b"\\'");
+                start = sout.len();
+            } else {
+                if !quoting {
+                    sout.insert(start, b'\'');
+                    quoting = true;
+                }
+                sout.push(ch);
+            }
+        }
+        if quoting {
+            sout.push(b'\'');
+        }
+    }
+
+    /// `(q+)`.
+    pub fn quoted_zputs(sin: &[u8], sout: &mut Vec<u8>) {
+        if sin.is_empty() {
+            sout.extend(b"''");
+        } else if !sin.iter().copied().all(is_printable) {
+            sout.extend(b"$'");
+            for &ch in sin {
+                match ch {
+                    b'\\' | b'\'' => sout.extend([b'\\', ch]),
+                    ch => nice_char(ch, sout),
+                }
+            }
+            sout.push(b'\'');
+        } else if !sin.iter().copied().any(is_special) {
+            sout.extend(sin);
+        } else {
+            // Bourne-style quoting, avoiding empty quoted strings.
+            let mut quoting = false;
+            for &ch in sin {
+                if ch == b'\'' {
+                    if quoting {
+                        sout.push(b'\'');
+                        quoting = false;
+                    }
+                    sout.extend(b"\\'");
+                } else {
+                    if !quoting {
+                        sout.push(b'\'');
+                        quoting = true;
+                    }
+                    sout.push(ch);
+                }
+            }
+            if quoting {
+                sout.push(b'\'');
+            }
+        }
+    }
+
+    /// Z Shell's `addunprintable`, for one byte, followed by `next`. `NUL` is
+    /// written as `\0` unless an octal digit follows.
+    fn unprintable(ch: u8, next: Option<&u8>, sout: &mut Vec<u8>) {
+        match ch {
+            0 if matches!(next, Some(b'0'..=b'7')) => sout.extend(b"\\000"),
+            0 => sout.extend(b"\\0"),
+            0x07 => sout.extend(b"\\a"),
+            0x08 => sout.extend(b"\\b"),
+            0x0C => sout.extend(b"\\f"),
+            b'\n' => sout.extend(b"\\n"),
+            b'\r' => sout.extend(b"\\r"),
+            b'\t' => sout.extend(b"\\t"),
+            0x0B => sout.extend(b"\\v"),
+            ch => sout.extend([
+                b'\\',
+                b'0' + (ch >> 6),
+                b'0' + (ch >> 3 & 7),
+                b'0' + (ch & 7),
+            ]),
+        }
+    }
+
+    /// Z Shell's `nicechar`, quotable, for one byte. Bytes of 0x80 and above
+    /// are not characters in the `C` locale, so are written as `\M-` and the
+    /// byte without its top bit.
+    fn nice_char(ch: u8, sout: &mut Vec<u8>) {
+        if ch & 0x80 != 0 {
+            sout.extend(b"\\M-");
+        }
+        match ch & 0x7F {
+            0x7F => sout.extend(b"\\C-?"),
+            b'\n' => sout.extend(b"\\n"),
+            b'\t' => sout.extend(b"\\t"),
+            ch @ 0..=0x1F => sout.extend([b'\\', b'C', b'-', ch + 0x40]),
+            ch => sout.push(ch),
+        }
+    }
+}
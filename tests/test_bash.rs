@@ -1,4 +1,4 @@
-#![cfg(feature = "bash")]
+#![cfg(all(feature = "bash", feature = "std"))]
 
 mod resources;
 mod util;
@@ -6,6 +6,7 @@ mod util;
 // -- impl Bash ---------------------------------------------------------------
 
 mod bash_impl {
+    use std::borrow::Cow;
     use std::ffi::OsString;
 
     use super::{
@@ -86,6 +87,150 @@ mod bash_impl {
         assert_eq!(Bash::quote_vec("Hello 👋"), b"$'Hello \xf0\x9f\x91\x8b'");
     }
 
+    #[test]
+    fn test_join() {
+        assert_eq!(Bash::join_vec(["foo", "foo bar"]), b"foo $'foo bar'");
+        assert_eq!(Bash::join_vec([""]), b"''");
+        assert_eq!(Bash::join_vec(["", ""]), b"'' ''");
+        assert_eq!(Bash::join_vec(Vec::<&[u8]>::new()), b"");
+        let mut buffer = b"exec".to_vec();
+        Bash::join_into_vec(Vec::<&str>::new(), &mut buffer);
+        assert_eq!(buffer, b"exec");
+        buffer.push(b' ');
+        Bash::join_into_vec(["foo", "foo bar"], &mut buffer);
+        assert_eq!(buffer, b"exec foo $'foo bar'");
+    }
+
+    #[test]
+    fn test_quote_cow() {
+        let inert = "foo/bar-1.2";
+        match Bash::quote_cow(inert) {
+            Cow::Borrowed(quoted) => assert!(std::ptr::eq(quoted, inert.as_bytes())),
+            Cow::Owned(_) => panic!("inert input was copied"),
+        }
+        assert!(matches!(Bash::quote_cow(""), Cow::Borrowed(b"''")));
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foo bar"[..],
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Bash::quote_cow(sample);
+            assert!(matches!(quoted, Cow::Owned(_)));
+            assert_eq!(quoted, Bash::quote_vec(sample));
+        }
+        assert_eq!(
+            Bash::quote_cow(resources::UTF8_SAMPLE),
+            Bash::quote_vec(resources::UTF8_SAMPLE)
+        );
+    }
+
+    #[test]
+    fn test_quote_cow_str() {
+        let inert = "foo/bar-1.2";
+        match Bash::quote_cow_str(inert) {
+            Cow::Borrowed(quoted) => assert!(std::ptr::eq(quoted, inert)),
+            Cow::Owned(_) => panic!("inert input was copied"),
+        }
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foo bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Bash::quote_cow_str(sample);
+            assert_eq!(quoted.as_bytes(), Bash::quote_vec(sample));
+        }
+        let quoted = Bash::quote_cow_str(resources::UTF8_SAMPLE);
+        assert_eq!(quoted.as_bytes(), Bash::quote_vec(resources::UTF8_SAMPLE));
+    }
+
+    #[test]
+    fn test_needs_quoting() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo/bar-1.2",
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(
+                Bash::needs_quoting(sample),
+                Bash::quote_vec(sample) != sample,
+                "{sample:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foobar"[..],
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let why = Bash::explain(sample);
+            assert_eq!(!why.is_empty(), Bash::needs_quoting(sample), "{sample:?}");
+            for explanation in why {
+                let ch = shell_quote::Char::from_byte(sample[explanation.position()]);
+                assert_eq!(explanation.char(), ch);
+                assert!(!ch.is_inert());
+            }
+        }
+        assert!(Bash::explain("").is_empty());
+        let why = Bash::explain("a b|c");
+        let positions: Vec<_> = why.iter().map(|why| why.position()).collect();
+        assert_eq!(positions, [1, 3]);
+        assert_eq!(why[0].to_string(), "` ` at 1: whitespace separates words");
+        assert_eq!(
+            why[1].to_string(),
+            "`|` at 3: control operator or redirection"
+        );
+    }
+
+    #[test]
+    fn test_quoted_len() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(Bash::quoted_len(sample), Bash::quote_vec(sample).len());
+        }
+        assert_eq!(
+            Bash::quoted_len(resources::UTF8_SAMPLE),
+            Bash::quote_vec(resources::UTF8_SAMPLE).len()
+        );
+    }
+
+    #[test]
+    fn test_quote_into_slice() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [&b""[..], b"foobar", b"foo bar", b"it's", &bytes] {
+            let expected = Bash::quote_vec(sample);
+            let mut buf = vec![0u8; expected.len() + 1];
+            let len = Bash::quote_into_slice(sample, &mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let len = Bash::quote_into_slice(sample, &mut buf[..expected.len()]).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let err = Bash::quote_into_slice(sample, &mut buf[..expected.len() - 1]).unwrap_err();
+            assert_eq!(err.needed(), expected.len());
+            assert_eq!(err.available(), expected.len() - 1);
+        }
+    }
+
     #[test]
     fn test_escape_into_plain() {
         let mut buffer = Vec::new();
@@ -166,6 +311,489 @@ mod bash_impl {
     }
 }
 
+// -- Bash::display ----------------------------------------------------------
+
+mod bash_display {
+    use std::fmt::{self, Write};
+
+    use shell_quote::Bash;
+
+    use super::resources;
+
+    fn samples() -> Vec<String> {
+        let ascii: String = (0..=0x7Fu8).map(char::from).collect();
+        let mut samples: Vec<String> = ["", "foobar", "foo bar", "it's", "'\"'"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        samples.extend(ascii.chars().map(String::from));
+        samples.push(ascii);
+        samples.push(resources::UTF8_SAMPLE.into());
+        samples
+    }
+
+    #[test]
+    fn test_display_matches_quote_vec() {
+        for sample in samples() {
+            let displayed = Bash::display(sample.as_str()).to_string();
+            assert_eq!(displayed.as_bytes(), Bash::quote_vec(&sample), "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn test_display_bytes_matches_quote_vec() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for byte in &bytes {
+            let displayed = Bash::display(&[*byte]).to_string();
+            assert_eq!(displayed.as_bytes(), Bash::quote_vec(&[*byte]));
+        }
+        let displayed = Bash::display(&bytes).to_string();
+        assert_eq!(displayed.as_bytes(), Bash::quote_vec(&bytes));
+    }
+
+    #[test]
+    fn test_debug_matches_display() {
+        for sample in samples() {
+            let quoted = Bash::display(sample.as_str());
+            assert_eq!(format!("{quoted:?}"), format!("{quoted}"));
+        }
+    }
+
+    #[test]
+    fn test_display_in_format() {
+        assert_eq!(
+            format!("cat {} -", Bash::display("foo bar")),
+            "cat $'foo bar' -"
+        );
+    }
+
+    #[test]
+    fn test_quote_into_fmt() {
+        for sample in samples() {
+            let mut buf = String::from("> ");
+            Bash::quote_into_fmt(sample.as_str(), &mut buf).unwrap();
+            let mut expected = b"> ".to_vec();
+            Bash::quote_into_vec(&sample, &mut expected);
+            assert_eq!(buf.as_bytes(), expected);
+        }
+    }
+
+    #[test]
+    fn test_quote_into_fmt_passes_on_errors() {
+        struct Broken;
+        impl Write for Broken {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+        assert!(Bash::quote_into_fmt("foobar", &mut Broken).is_err());
+        assert!(Bash::quote_into_fmt("foo bar", &mut Broken).is_err());
+    }
+}
+
+// -- Bash::quote_into_writer ------------------------------------------------
+
+mod bash_writer {
+    use std::io::{self, Write};
+
+    use shell_quote::Bash;
+
+    use super::resources;
+
+    /// A writer that accepts `limit` bytes, then fails.
+    struct Limited {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let room = self.limit - self.written.len();
+            if room == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            let len = buf.len().min(room);
+            self.written.extend(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_quote_into_writer_matches_quote_vec() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let samples: [&[u8]; 6] = [
+            b"",
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ];
+        for sample in samples {
+            let mut buf = b"> ".to_vec();
+            Bash::quote_into_writer(sample, &mut buf).unwrap();
+            assert_eq!(buf, [&b"> "[..], &Bash::quote_vec(sample)].concat());
+        }
+        let mut buf = Vec::new();
+        Bash::quote_into_writer(resources::UTF8_SAMPLE, &mut buf).unwrap();
+        assert_eq!(buf, Bash::quote_vec(resources::UTF8_SAMPLE));
+    }
+
+    #[test]
+    fn test_join_into_writer_matches_join_vec() {
+        let args = ["ls", "-l", "foo bar", "", "it's"];
+        let mut buf = Vec::new();
+        Bash::join_into_writer(args, &mut buf).unwrap();
+        assert_eq!(buf, Bash::join_vec(args));
+        let mut buf = Vec::new();
+        Bash::join_into_writer(Vec::<&str>::new(), &mut buf).unwrap();
+        assert_eq!(buf, b"");
+    }
+
+    #[test]
+    fn test_quote_into_writer_passes_on_errors() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let expected = Bash::quote_vec(&bytes);
+        for limit in [0, 1, 2, expected.len() / 2, expected.len() - 1] {
+            let mut writer = Limited {
+                written: Vec::new(),
+                limit,
+            };
+            let err = Bash::quote_into_writer(&bytes, &mut writer).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+            // Everything up to the failure was written.
+            assert_eq!(writer.written, expected[..limit]);
+        }
+        let mut writer = Limited {
+            written: Vec::new(),
+            limit: 5,
+        };
+        let err = Bash::join_into_writer(["foo", "bar"], &mut writer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
+
+// -- Bash::printf_q & Bash::at_q -------------------------------------------
+
+mod bash_builtin {
+    use super::resources;
+    use shell_quote::Bash;
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut samples: Vec<Vec<u8>> = Vec::new();
+        // Each byte at the start of a word, and after another character.
+        for ch in 1..=u8::MAX {
+            samples.push(vec![ch]);
+            samples.push(vec![b'x', ch]);
+        }
+        samples.push((1..=u8::MAX).collect());
+        samples.push(resources::UTF8_SAMPLE.into());
+        for sample in [
+            "", "foobar", "foo bar", "'", "''", "it's", "~", "~/bin", "a:~", "a=~b", "a~", "#a",
+            "a#", "%1", "=ls", "\\", " ~", "a ~", "x=~",
+        ] {
+            samples.push(sample.into());
+        }
+        samples
+    }
+
+    #[test]
+    fn test_printf_q() {
+        assert_eq!(Bash::printf_q(""), b"''");
+        assert_eq!(Bash::printf_q("foo"), b"foo");
+        assert_eq!(Bash::printf_q("a b#~"), b"a\\ b#~");
+        assert_eq!(Bash::printf_q("#~a:~b=~"), b"\\#~a:\\~b=\\~");
+        assert_eq!(Bash::printf_q("it's"), b"it\\'s");
+        assert_eq!(Bash::printf_q("it's\n"), b"$'it\\'s\\n'");
+        assert_eq!(
+            Bash::printf_q("\0\x1B\x7F\u{e9}"),
+            b"$'\\000\\E\\177\\303\\251'"
+        );
+    }
+
+    #[test]
+    fn test_at_q() {
+        assert_eq!(Bash::at_q(""), b"''");
+        assert_eq!(Bash::at_q("foo"), b"'foo'");
+        assert_eq!(Bash::at_q("'"), b"\\'");
+        assert_eq!(Bash::at_q("''"), b"''\\'''\\'''");
+        assert_eq!(Bash::at_q("it's"), b"'it'\\''s'");
+        assert_eq!(Bash::at_q("it's\n"), b"$'it\\'s\\n'");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for sample in samples() {
+            assert_eq!(Bash::unquote(&Bash::printf_q(&sample)).unwrap(), sample);
+            assert_eq!(Bash::unquote(&Bash::at_q(&sample)).unwrap(), sample);
+        }
+    }
+
+    /// Bash 5.x writes the same, in the `C` locale.
+    #[cfg(unix)]
+    #[test]
+    fn test_agrees_with_bash() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        for bin in find_bins("bash") {
+            let version = invoke_shell(&bin, "printf %s $BASH_VERSINFO".as_ref()).unwrap();
+            if version.stdout < b"5".to_vec() {
+                continue; // `${var@Q}` is from Bash 4.4, and `printf %q` differs.
+            }
+            for sample in samples() {
+                let mut script = b"export LC_ALL=C\nx=".to_vec();
+                Bash::quote_into_vec(&sample, &mut script);
+                script.extend(b"\nprintf '%q\\0%s' \"$x\" \"${x@Q}\"");
+                let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                let expected = [Bash::printf_q(&sample), Bash::at_q(&sample)].join(&0);
+                assert_eq!(output.stdout, expected, "{bin:?} {sample:?}");
+            }
+        }
+    }
+}
+
+// -- Bash::unquote -----------------------------------------------------------
+
+mod bash_unquote {
+    use super::resources;
+    use shell_quote::{Bash, UnquoteErrorKind};
+
+    #[test]
+    fn test_unquote_segments() {
+        assert_eq!(Bash::unquote("foobar").unwrap(), b"foobar");
+        assert_eq!(Bash::unquote("''").unwrap(), b"");
+        assert_eq!(Bash::unquote("$''").unwrap(), b"");
+        assert_eq!(Bash::unquote("$'foo bar'").unwrap(), b"foo bar");
+        assert_eq!(
+            Bash::unquote(r#"a\ b'c d'"e \"f\""$'\tg'"#).unwrap(),
+            b"a bc de \"f\"\tg"
+        );
+    }
+
+    #[test]
+    fn test_unquote_ansi_c_escapes() {
+        assert_eq!(
+            Bash::unquote(r#"$'\a\b\e\E\f\n\r\t\v\\\'\"\?'"#).unwrap(),
+            b"\x07\x08\x1B\x1B\x0C\n\r\t\x0B\\'\"?"
+        );
+        assert_eq!(Bash::unquote(r"$'\0\101\777\08'").unwrap(), b"\0A\xFF\x008");
+        assert_eq!(
+            Bash::unquote(r"$'\x41\x4142\xg\x'").unwrap(),
+            b"AA42\\xg\\x"
+        );
+        assert_eq!(
+            Bash::unquote(r"$'é\U0001F600\u'").unwrap(),
+            "é😀\\u".as_bytes()
+        );
+        assert_eq!(
+            Bash::unquote(r"$'\cA\ca\c?\c[\c\\'").unwrap(),
+            b"\x01\x01\x7F\x1B\x1C"
+        );
+        assert_eq!(Bash::unquote(r"$'\c'").unwrap(), b"\\c");
+        // Unrecognised escapes are kept as they are.
+        assert_eq!(Bash::unquote(r"$'\z\%'").unwrap(), b"\\z\\%");
+    }
+
+    #[test]
+    fn test_unquote_errors() {
+        let err = Bash::unquote("$'foo").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedAnsiCQuote);
+        assert_eq!(err.position(), 0);
+        let err = Bash::unquote(r"a$'foo\'").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedAnsiCQuote);
+        assert_eq!(err.position(), 1);
+        let err = Bash::unquote(r"$'\ud800'").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::InvalidEscape);
+        assert_eq!(err.position(), 2);
+        let err = Bash::unquote("foo'bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedSingleQuote);
+        let err = Bash::unquote("\"foo").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedDoubleQuote);
+        let err = Bash::unquote("foo\\").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::TrailingBackslash);
+        for (input, position) in [
+            ("foo$bar", 3),
+            ("${foo}", 0),
+            ("$\"foo\"", 0),
+            ("`foo`", 0),
+            ("\"$(foo)\"", 1),
+            ("*.txt", 0),
+            ("a?", 1),
+            ("~root", 0),
+            ("{a,b}", 0),
+            ("!!", 0),
+        ] {
+            let err = Bash::unquote(input).unwrap_err();
+            assert!(
+                matches!(err.kind(), UnquoteErrorKind::Expansion(_)),
+                "{input:?} -> {err}"
+            );
+            assert_eq!(err.position(), position, "{input:?} -> {err}");
+        }
+        let err = Bash::unquote("foo bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Metacharacter(b' '));
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_each_byte() {
+        for ch in u8::MIN..=u8::MAX {
+            let quoted = Bash::quote_vec(&[ch]);
+            assert_eq!(Bash::unquote(&quoted).unwrap(), [ch], "{quoted:?}");
+        }
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_all_bytes() {
+        let input: Vec<u8> = (u8::MIN..=u8::MAX).collect();
+        let quoted = Bash::quote_vec(&input);
+        assert_eq!(Bash::unquote(&quoted).unwrap(), input);
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_utf8() {
+        let quoted = Bash::quote_vec(resources::UTF8_SAMPLE);
+        assert_eq!(
+            Bash::unquote(&quoted).unwrap(),
+            resources::UTF8_SAMPLE.as_bytes()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unquote_agrees_with_bash() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let words = [
+            r#"a\ b'c d'"e \"f\""$'\tg'"#,
+            r#"$'\a\b\e\E\f\n\r\t\v\\\'\"\?'"#,
+            // Not NUL: Bash truncates at it; see the warning on `Bash`.
+            r"$'\101\777\18'",
+            r"$'\x41\x4142\xg\x'",
+            r"$'\cA\ca\c?\c[\c\\\c1'",
+            r"$'\z\%\c'",
+            r#""\a\$\`\\""#,
+        ];
+        for bin in find_bins("bash") {
+            for word in words {
+                let script = OsString::from_vec(format!("printf %s {word}").into_bytes());
+                let output = invoke_shell(&bin, &script).unwrap();
+                assert_eq!(Bash::unquote(word).unwrap(), output.stdout, "{word:?}");
+            }
+        }
+    }
+}
+
+// -- Bash::split -----------------------------------------------------------
+
+mod bash_split {
+    use shell_quote::{Bash, UnquoteErrorKind};
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(Bash::split("").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Bash::split(" \t ").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Bash::split("foo").unwrap(), [b"foo"]);
+        assert_eq!(
+            Bash::split("  foo\tbar  baz ").unwrap(),
+            [&b"foo"[..], b"bar", b"baz"]
+        );
+        assert_eq!(Bash::split("'' foo ''").unwrap(), [&b""[..], b"foo", b""]);
+        assert_eq!(
+            Bash::split(r#"a' b'"c d"\ e $'x\ty'"#).unwrap(),
+            [&b"a bc d e"[..], b"x\ty"]
+        );
+    }
+
+    #[test]
+    fn test_split_comments_and_newlines() {
+        assert_eq!(Bash::split("foo # bar baz").unwrap(), [b"foo"]);
+        assert_eq!(Bash::split("# bar baz").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Bash::split("foo#bar").unwrap(), [b"foo#bar"]);
+        assert_eq!(Bash::split("foo\n").unwrap(), [b"foo"]);
+        assert_eq!(Bash::split("foo # bar\n  \n# baz\n").unwrap(), [b"foo"]);
+        assert_eq!(Bash::split("foo \\\n bar").unwrap(), [b"foo", b"bar"]);
+        let err = Bash::split("foo\nbar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'\n'));
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn test_split_operators() {
+        for (input, op, position) in [
+            ("ls | wc", b'|', 3),
+            ("a; b", b';', 1),
+            ("a && b", b'&', 2),
+            ("a &", b'&', 2),
+            ("a > b", b'>', 2),
+            ("a 2>b", b'>', 3),
+            ("a <b", b'<', 2),
+            ("(a)", b'(', 0),
+        ] {
+            let err = Bash::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Operator(op), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_expansions() {
+        for (input, ch, position) in [
+            ("echo $HOME", b'$', 5),
+            ("ls *.txt", b'*', 3),
+            ("cd ~", b'~', 3),
+        ] {
+            let err = Bash::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Expansion(ch), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_roundtrip() {
+        let args: Vec<Vec<u8>> = vec![
+            b"rsync".to_vec(),
+            b"".to_vec(),
+            b"my dir/".to_vec(),
+            b"#not-a-comment".to_vec(),
+            b"a | b; c && d > e".to_vec(),
+            (u8::MIN..=u8::MAX).collect(),
+            "Hello 👋".into(),
+        ];
+        assert_eq!(Bash::split(&Bash::join_vec(&args)).unwrap(), args);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_agrees_with_bash() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let lines = [
+            r#"a' b'"c d"\ e $'x\ty'"#,
+            "foo # bar baz",
+            "foo#bar \\\n baz",
+            "'' x ''",
+        ];
+        for bin in find_bins("bash") {
+            for line in lines {
+                let script = format!("printf '%s\\0' {line}");
+                let output = invoke_shell(&bin, &OsString::from_vec(script.into_bytes())).unwrap();
+                let mut expected: Vec<_> = output
+                    .stdout
+                    .split(|&ch| ch == 0)
+                    .map(<[u8]>::to_vec)
+                    .collect();
+                expected.pop(); // Trailing terminator.
+                assert_eq!(Bash::split(line).unwrap(), expected, "{line:?}");
+            }
+        }
+    }
+}
+
 // -- QuoteExt ----------------------------------------------------------------
 
 mod bash_quote_ext {
@@ -179,6 +807,14 @@ mod bash_quote_ext {
         assert_eq!(string, "Hello, $'World, Bob, !@#$%^&*(){}[]'");
     }
 
+    #[test]
+    fn test_vec_push_quoted_join_with_bash() {
+        let mut buffer = Vec::from(b"exec ");
+        buffer.push_quoted_join(Bash, ["foo", "foo bar", ""]);
+        let string = String::from_utf8(buffer).unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo $'foo bar' ''");
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_os_string_push_quoted_with_bash() {
@@ -190,6 +826,25 @@ mod bash_quote_ext {
         assert_eq!(string, "Hello, $'World, Bob, !@#$%^&*(){}[]'");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_os_string_push_quoted_join_with_bash() {
+        use std::ffi::OsString;
+
+        let mut buffer: OsString = "exec ".into();
+        buffer.push_quoted_join(Bash, ["foo", "foo bar", ""]);
+        let string = buffer.into_string().unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo $'foo bar' ''");
+    }
+
+    #[cfg(feature = "bstr")]
+    #[test]
+    fn test_bstring_push_quoted_join_with_bash() {
+        let mut string: bstr::BString = "exec ".into();
+        string.push_quoted_join(Bash, ["foo", "foo bar", ""]);
+        assert_eq!(string, "exec foo $'foo bar' ''");
+    }
+
     #[cfg(feature = "bstr")]
     #[test]
     fn test_bstring_push_quoted_with_bash() {
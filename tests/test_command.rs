@@ -0,0 +1,168 @@
+#![cfg(all(
+    unix,
+    feature = "sh",
+    feature = "bash",
+    feature = "fish",
+    feature = "std"
+))]
+
+mod util;
+
+use std::{ffi::OsString, os::unix::ffi::OsStringExt, process::Command};
+
+use shell_quote::{Bash, CommandExt, CommandSyntax, EnvClear, Fish, Sh};
+use test_case::test_case;
+
+use util::{find_bins, invoke_shell};
+
+/// A command that reports on its environment, arguments, and working
+/// directory.
+fn reporter() -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(r#"printf '%s|' "${FOO-unset}" "${HOME-unset}" "$PWD" "$0" "$1""#)
+        .arg("zero")
+        .arg("one 'two'");
+    command
+}
+
+#[test]
+fn test_program_and_args() {
+    let mut command = Command::new("ls");
+    command.arg("-l").arg("my dir").arg("");
+    assert_eq!(command.to_shell::<Sh>(), b"ls -l my' dir' ''");
+    assert_eq!(command.to_shell::<Bash>(), b"ls -l $'my dir' ''");
+    assert_eq!(command.to_shell::<Fish>(), b"ls -l my' dir' ''");
+}
+
+#[test]
+fn test_current_dir() {
+    let mut command = Command::new("ls");
+    command.current_dir("/path/to/my dir");
+    assert_eq!(command.to_shell::<Sh>(), b"cd /path/to/my' dir' && ls");
+    assert_eq!(command.to_shell::<Bash>(), b"cd $'/path/to/my dir' && ls");
+    assert_eq!(command.to_shell::<Fish>(), b"cd /path/to/my' dir' && ls");
+}
+
+#[test]
+fn test_env() {
+    let mut command = Command::new("ls");
+    command.env("FOO", "foo bar").env("BAR", "bar");
+    assert_eq!(command.to_shell::<Sh>(), b"BAR=bar FOO=foo' bar' ls");
+    assert_eq!(command.to_shell::<Bash>(), b"BAR=bar FOO=$'foo bar' ls");
+    assert_eq!(
+        command.to_shell::<Fish>(),
+        b"env BAR'=bar' FOO'=foo bar' ls"
+    );
+}
+
+#[test]
+fn test_env_with_invalid_name() {
+    let mut command = Command::new("ls");
+    command.env("FOO.BAR", "baz");
+    assert_eq!(command.to_shell::<Sh>(), b"env FOO.BAR'=baz' ls");
+    assert_eq!(command.to_shell::<Bash>(), b"env $'FOO.BAR=baz' ls");
+}
+
+#[test]
+fn test_env_remove() {
+    let mut command = Command::new("ls");
+    command.env_remove("FOO").env("BAR", "bar");
+    assert_eq!(command.to_shell::<Sh>(), b"env -u FOO BAR'=bar' ls");
+    assert_eq!(command.to_shell::<Bash>(), b"env -u FOO $'BAR=bar' ls");
+    assert_eq!(command.to_shell::<Fish>(), b"env -u FOO BAR'=bar' ls");
+}
+
+#[test]
+fn test_env_clear() {
+    let mut command = Command::new("ls");
+    command.env_clear().env("BAR", "bar").current_dir("/tmp");
+    assert_eq!(
+        command.to_shell_with::<Sh>(EnvClear::Yes),
+        b"cd /tmp && env -i BAR'=bar' ls"
+    );
+    assert_eq!(
+        command.to_shell_with::<Bash>(EnvClear::Yes),
+        b"cd /tmp && env -i $'BAR=bar' ls"
+    );
+    assert_eq!(
+        command.to_shell_with::<Fish>(EnvClear::Yes),
+        b"cd /tmp && env -i BAR'=bar' ls"
+    );
+    // `to_shell` cannot see that `env_clear` was called.
+    assert_eq!(command.to_shell::<Sh>(), b"cd /tmp && BAR=bar ls");
+}
+
+#[test]
+fn test_program_with_equals() {
+    let mut command = Command::new("a=b");
+    command.arg("c=d");
+    assert_eq!(command.to_shell::<Sh>(), b"a'=b' c'=d'");
+    command.env_remove("X");
+    assert_eq!(
+        command.to_shell::<Sh>(),
+        b"env -u X sh -c exec' \"$@\"' sh a'=b' c'=d'"
+    );
+    assert_eq!(
+        command.to_shell::<Bash>(),
+        b"env -u X sh -c $'exec \"$@\"' sh $'a=b' $'c=d'"
+    );
+    assert_eq!(
+        command.to_shell::<Fish>(),
+        b"env -u X sh -c exec' \"$@\"' sh a'=b' c'=d'"
+    );
+}
+
+/// Run `command` directly, and again via its rendering in `shell`, and check
+/// that they agree.
+fn check_agrees<Q: CommandSyntax>(shell: &str, env_clear: EnvClear, command: &mut Command) {
+    let expected = command.output().unwrap();
+    assert!(expected.status.success());
+    let script = OsString::from_vec(command.to_shell_with::<Q>(env_clear));
+    for bin in find_bins(shell) {
+        let observed = invoke_shell(&bin, &script).unwrap();
+        assert_eq!(
+            String::from_utf8(observed.stdout).unwrap(),
+            String::from_utf8(expected.stdout.clone()).unwrap(),
+            "{script:?} in {bin:?}"
+        );
+    }
+}
+
+#[test_case(check_agrees::<Sh>, "sh")]
+#[test_case(check_agrees::<Sh>, "dash")]
+#[test_case(check_agrees::<Bash>, "bash")]
+#[test_case(check_agrees::<Bash>, "zsh")]
+#[test_case(check_agrees::<Fish>, "fish")]
+fn test_roundtrip(check: fn(&str, EnvClear, &mut Command), shell: &str) {
+    let dir = std::env::temp_dir();
+    let no = EnvClear::No;
+    check(
+        shell,
+        no,
+        reporter().env("FOO", "it's $HOME").current_dir(&dir),
+    );
+    check(shell, no, reporter().env("FOO", "foo").env_remove("HOME"));
+    let yes = EnvClear::Yes;
+    check(shell, yes, reporter().env_clear().env("FOO", "foo bar"));
+}
+
+#[test_case(check_agrees::<Sh>, "sh")]
+#[test_case(check_agrees::<Bash>, "bash")]
+#[test_case(check_agrees::<Fish>, "fish")]
+fn test_roundtrip_program_with_equals(check: fn(&str, EnvClear, &mut Command), shell: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("shell-quote-{shell}-a=b"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("report");
+    std::fs::write(
+        &program,
+        "#!/bin/sh\nprintf '%s|' \"${FOO-unset}\" \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let mut command = Command::new(&program);
+    command.arg("x=y").env("FOO", "foo").env_remove("HOME");
+    check(shell, EnvClear::No, &mut command);
+}
@@ -0,0 +1,169 @@
+#![cfg(all(feature = "sh", feature = "std"))]
+
+mod resources;
+mod util;
+
+use shell_quote::{Join, Jq, QuoteRefExt, Shellwords, Shlex};
+
+fn samples() -> Vec<String> {
+    let mut samples: Vec<String> = Vec::new();
+    // Each ASCII character at the start of a word, and after another.
+    for ch in '\x01'..='\x7F' {
+        samples.push(ch.into());
+        samples.push(format!("x{ch}"));
+    }
+    samples.push(('\x01'..='\x7F').collect());
+    samples.push(resources::UTF8_SAMPLE.into());
+    for sample in ["", "foo bar", "it's", "'", "''", "a\nb", "café", "--x=%y"] {
+        samples.push(sample.into());
+    }
+    samples
+}
+
+// -- Shlex -------------------------------------------------------------------
+
+#[test]
+fn test_shlex() {
+    let quote = |s: &str| -> Vec<u8> { s.quoted(Shlex) };
+    assert_eq!(quote(""), b"''");
+    assert_eq!(quote("foo/bar-1.2"), b"foo/bar-1.2");
+    assert_eq!(quote("a%b=c"), b"a%b=c");
+    assert_eq!(quote("foo bar"), b"'foo bar'");
+    assert_eq!(quote("it's"), br#"'it'"'"'s'"#);
+    assert_eq!(quote("'"), br#"''"'"''"#);
+    assert_eq!(quote("café"), "'café'".as_bytes());
+    let joined: Vec<u8> = Shlex::join(["echo", "it's", ""]);
+    assert_eq!(joined, br#"echo 'it'"'"'s' ''"#);
+}
+
+// -- Jq ----------------------------------------------------------------------
+
+#[test]
+fn test_jq() {
+    let quote = |s: &str| -> Vec<u8> { s.quoted(Jq) };
+    assert_eq!(quote(""), b"''");
+    assert_eq!(quote("foo"), b"'foo'");
+    assert_eq!(quote("foo bar"), b"'foo bar'");
+    assert_eq!(quote("it's"), br"'it'\''s'");
+    assert_eq!(quote("'"), br"''\'''");
+}
+
+// -- Shellwords --------------------------------------------------------------
+
+#[test]
+fn test_shellwords() {
+    let quote = |s: &str| -> Vec<u8> { s.quoted(Shellwords) };
+    assert_eq!(quote(""), b"''");
+    assert_eq!(quote("foo/bar-1.2+x@y:z"), b"foo/bar-1.2+x@y:z");
+    assert_eq!(quote("it's here"), br"it\'s\ here");
+    assert_eq!(quote("a%b=c"), br"a\%b\=c");
+    assert_eq!(quote("a\nb"), b"a'\n'b");
+    assert_eq!(quote("a\tb"), b"a\\\tb");
+    assert_eq!(quote("café"), "caf\\é".as_bytes());
+    let quoted: Vec<u8> = b"caf\xC3\xA9".quoted(Shellwords);
+    assert_eq!(quoted, b"caf\\\xC3\\\xA9");
+}
+
+// -- Inherent functions ------------------------------------------------------
+
+#[test]
+fn test_inherent_functions_agree() {
+    for sample in samples() {
+        let sample = sample.as_str();
+        macro_rules! check {
+            ($q:ident) => {{
+                let quoted: Vec<u8> = sample.quoted($q);
+                assert_eq!($q::quote_vec(sample), quoted, "{sample:?}");
+                assert_eq!($q::quoted_len(sample), quoted.len(), "{sample:?}");
+                assert_eq!($q::display(sample).to_string().as_bytes(), quoted);
+                assert_eq!(*$q::quote_cow(sample), *quoted, "{sample:?}");
+                assert_eq!(
+                    $q::needs_quoting(sample),
+                    quoted != sample.as_bytes(),
+                    "{sample:?}"
+                );
+                let mut buf = vec![0; quoted.len()];
+                assert_eq!($q::quote_into_slice(sample, &mut buf), Ok(quoted.len()));
+                assert_eq!(buf, quoted);
+                let mut written = Vec::new();
+                $q::quote_into_writer(sample, &mut written).unwrap();
+                assert_eq!(written, quoted);
+            }};
+        }
+        check!(Shlex);
+        check!(Jq);
+        check!(Shellwords);
+    }
+    assert_eq!(Jq::join_vec(["ls", ""]), b"'ls' ''");
+    assert_eq!(Shellwords::join_vec(["ls", "a b"]), br"ls a\ b");
+}
+
+// -- Round trips and agreement -------------------------------------------------
+
+#[cfg(unix)]
+mod agreement {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    use std::process::Command;
+
+    use super::samples;
+    use super::util::{find_bins, invoke_shell};
+    use shell_quote::{Jq, Quote, QuoteRefExt, Shellwords, Shlex};
+
+    /// Run every `bin` on `PATH` with `args` and every sample, and check that
+    /// it writes each sample, quoted by `Q`, separated by NUL. Each sample is
+    /// given with an `x` in front, so that none is read as an option; `args`
+    /// must take it off again.
+    fn check<Q: Quote<Vec<u8>>>(name: &str, args: &[&str]) {
+        let samples = samples();
+        let expected: Vec<Vec<u8>> = samples.iter().map(Q::quote).collect();
+        let samples = samples.iter().map(|sample| format!("x{sample}"));
+        for bin in find_bins(name) {
+            let output = Command::new(&bin)
+                .args(args)
+                .args(samples.clone())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{bin:?}");
+            let observed: Vec<&[u8]> = output.stdout.split(|&ch| ch == 0).collect();
+            assert_eq!(observed, expected, "{bin:?}");
+        }
+    }
+
+    #[test]
+    fn test_shlex_agrees_with_python() {
+        let script = r"import shlex, sys; print(*(shlex.quote(arg[1:]) for arg in sys.argv[1:]), sep='\0', end='')";
+        check::<Shlex>("python3", &["-c", script]);
+    }
+
+    #[test]
+    fn test_jq_agrees_with_jq() {
+        let filter = r#"$ARGS.positional | map(.[1:] | @sh) | join("\u0000")"#;
+        check::<Jq>("jq", &["-nj", filter, "--args"]);
+    }
+
+    #[test]
+    fn test_shellwords_agrees_with_ruby() {
+        let script = r#"require 'shellwords'; print ARGV.map { |arg| Shellwords.escape(arg[1..]) }.join("\0")"#;
+        check::<Shellwords>("ruby", &["-e", script]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for bin in find_bins("sh") {
+            for sample in samples() {
+                let quoted: [Vec<u8>; 3] = [
+                    sample.as_str().quoted(Shlex),
+                    sample.as_str().quoted(Jq),
+                    sample.as_str().quoted(Shellwords),
+                ];
+                for quoted in quoted {
+                    let mut script = b"printf %s ".to_vec();
+                    script.extend(quoted);
+                    let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                    assert_eq!(output.stdout, sample.as_bytes(), "{bin:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "std")]
+
+use shell_quote::{Char, Dialect, Escaped, QuoteExt, QuoteRefExt, QuoteWriteExt, Quoting, Render};
+
+/// A made-up language that quotes with `[` and `]`, escapes `]` and `\` with a
+/// backslash inside of them, and writes tab, newline, and other control
+/// characters as `^X` outside of them.
+struct Brackets;
+
+impl Dialect for Brackets {
+    const OPEN: &'static [u8] = b"[";
+    const CLOSE: &'static [u8] = b"]";
+    const EMPTY: &'static [u8] = b"[]";
+
+    fn render(ch: Char) -> Render {
+        match ch {
+            Char::PrintableInert(_) => Render::Literal(Quoting::Either),
+            Char::Printable(b']') => Render::Escape(Quoting::Inside, Escaped::new(b"\\]")),
+            Char::Backslash => Render::Escape(Quoting::Inside, Escaped::new(b"\\\\")),
+            Char::HorizontalTab => Render::Escape(Quoting::Outside, Escaped::new(b"^I")),
+            Char::NewLine => Render::Escape(Quoting::Outside, Escaped::new(b"^J")),
+            Char::Control(ch) => Render::Escape(Quoting::Outside, Escaped::new(&[b'^', ch + 0x40])),
+            _ => Render::Literal(Quoting::Inside),
+        }
+    }
+}
+
+// -- QuoteInto ---------------------------------------------------------------
+
+mod quote_into {
+    use super::Brackets;
+    use shell_quote::{JoinInto, Quote};
+
+    #[test]
+    fn test_quote() {
+        let quoted: Vec<u8> = Brackets::quote("");
+        assert_eq!(quoted, b"[]");
+        let quoted: Vec<u8> = Brackets::quote("foobar");
+        assert_eq!(quoted, b"foobar");
+        let quoted: Vec<u8> = Brackets::quote("foo bar");
+        assert_eq!(quoted, b"foo[ bar]");
+        let quoted: Vec<u8> = Brackets::quote("a]b\\c");
+        assert_eq!(quoted, b"a[\\]b\\\\c]");
+        let quoted: Vec<u8> = Brackets::quote("(one\ttwo)");
+        assert_eq!(quoted, b"[(one]^Itwo[)]");
+        let quoted: Vec<u8> = Brackets::quote("café");
+        assert_eq!(quoted, "caf[é]".as_bytes());
+    }
+
+    #[test]
+    fn test_join() {
+        let mut buf = b"run ".to_vec();
+        Brackets::join_into(["ls", "my dir", ""], &mut buf);
+        assert_eq!(buf, b"run ls my[ dir] []");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quote_into_os_string() {
+        let quoted: std::ffi::OsString = Brackets::quote("foo bar");
+        assert_eq!(quoted, "foo[ bar]");
+    }
+
+    #[cfg(feature = "bstr")]
+    #[test]
+    fn test_quote_into_bstring() {
+        let quoted: bstr::BString = Brackets::quote("foo bar");
+        assert_eq!(quoted, "foo[ bar]");
+    }
+}
+
+// -- Extension traits --------------------------------------------------------
+
+#[test]
+fn test_quote_ext() {
+    let mut buf = b"echo ".to_vec();
+    buf.push_quoted(Brackets, "foo bar");
+    buf.push(b' ');
+    buf.push_quoted_join(Brackets, ["a", "b c"]);
+    assert_eq!(buf, b"echo foo[ bar] a b[ c]");
+}
+
+#[test]
+fn test_quote_ref_ext() {
+    let quoted: Vec<u8> = "foo bar".quoted(Brackets);
+    assert_eq!(quoted, b"foo[ bar]");
+}
+
+#[test]
+fn test_quote_write_ext() {
+    let mut script = Vec::new();
+    script.write_quoted_join(Brackets, ["echo", "x]y"]).unwrap();
+    assert_eq!(script, b"echo x[\\]y]");
+}
+
+// -- Escaped -----------------------------------------------------------------
+
+#[test]
+fn test_escaped() {
+    let escaped = Escaped::new(b"\\x7F");
+    assert_eq!(escaped.as_bytes(), b"\\x7F");
+    assert_eq!(format!("{escaped:?}"), r#"Escaped("\\x7F")"#);
+    assert_eq!(
+        Escaped::new(&[b'.'; Escaped::CAPACITY]).as_bytes().len(),
+        16
+    );
+}
+
+#[test]
+#[should_panic(expected = "escape sequence is too long")]
+fn test_escaped_too_long() {
+    Escaped::new(&[b'.'; Escaped::CAPACITY + 1]);
+}
@@ -1,4 +1,4 @@
-#![cfg(feature = "fish")]
+#![cfg(all(feature = "fish", feature = "std"))]
 
 mod resources;
 mod util;
@@ -39,6 +39,7 @@ static FISH_VERSION_UNICODE_FIXED: semver::Version = semver::Version::new(3, 6,
 // -- impl Fish ---------------------------------------------------------------
 
 mod fish_impl {
+    use std::borrow::Cow;
     use std::ffi::OsString;
 
     use super::{
@@ -132,6 +133,150 @@ mod fish_impl {
         assert_eq!(Fish::quote_vec("A\nB"), b"A\\nB");
     }
 
+    #[test]
+    fn test_join() {
+        assert_eq!(Fish::join_vec(["foo", "foo bar"]), b"foo foo' bar'");
+        assert_eq!(Fish::join_vec([""]), b"''");
+        assert_eq!(Fish::join_vec(["", ""]), b"'' ''");
+        assert_eq!(Fish::join_vec(Vec::<&[u8]>::new()), b"");
+        let mut buffer = b"exec".to_vec();
+        Fish::join_into_vec(Vec::<&str>::new(), &mut buffer);
+        assert_eq!(buffer, b"exec");
+        buffer.push(b' ');
+        Fish::join_into_vec(["foo", "foo bar"], &mut buffer);
+        assert_eq!(buffer, b"exec foo foo' bar'");
+    }
+
+    #[test]
+    fn test_quote_cow() {
+        let inert = "foo/bar-1.2";
+        match Fish::quote_cow(inert) {
+            Cow::Borrowed(quoted) => assert!(std::ptr::eq(quoted, inert.as_bytes())),
+            Cow::Owned(_) => panic!("inert input was copied"),
+        }
+        assert!(matches!(Fish::quote_cow(""), Cow::Borrowed(b"''")));
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foo bar"[..],
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Fish::quote_cow(sample);
+            assert!(matches!(quoted, Cow::Owned(_)));
+            assert_eq!(quoted, Fish::quote_vec(sample));
+        }
+        assert_eq!(
+            Fish::quote_cow(resources::UTF8_SAMPLE),
+            Fish::quote_vec(resources::UTF8_SAMPLE)
+        );
+    }
+
+    #[test]
+    fn test_quote_cow_str() {
+        let inert = "foo/bar-1.2";
+        match Fish::quote_cow_str(inert) {
+            Cow::Borrowed(quoted) => assert!(std::ptr::eq(quoted, inert)),
+            Cow::Owned(_) => panic!("inert input was copied"),
+        }
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foo bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Fish::quote_cow_str(sample);
+            assert_eq!(quoted.as_bytes(), Fish::quote_vec(sample));
+        }
+        let quoted = Fish::quote_cow_str(resources::UTF8_SAMPLE);
+        assert_eq!(quoted.as_bytes(), Fish::quote_vec(resources::UTF8_SAMPLE));
+    }
+
+    #[test]
+    fn test_needs_quoting() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo/bar-1.2",
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(
+                Fish::needs_quoting(sample),
+                Fish::quote_vec(sample) != sample,
+                "{sample:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foobar"[..],
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let why = Fish::explain(sample);
+            assert_eq!(!why.is_empty(), Fish::needs_quoting(sample), "{sample:?}");
+            for explanation in why {
+                let ch = shell_quote::Char::from_byte(sample[explanation.position()]);
+                assert_eq!(explanation.char(), ch);
+                assert!(!ch.is_inert());
+            }
+        }
+        assert!(Fish::explain("").is_empty());
+        let why = Fish::explain("a b|c");
+        let positions: Vec<_> = why.iter().map(|why| why.position()).collect();
+        assert_eq!(positions, [1, 3]);
+        assert_eq!(why[0].to_string(), "` ` at 1: whitespace separates words");
+        assert_eq!(
+            why[1].to_string(),
+            "`|` at 3: control operator or redirection"
+        );
+    }
+
+    #[test]
+    fn test_quoted_len() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(Fish::quoted_len(sample), Fish::quote_vec(sample).len());
+        }
+        assert_eq!(
+            Fish::quoted_len(resources::UTF8_SAMPLE),
+            Fish::quote_vec(resources::UTF8_SAMPLE).len()
+        );
+    }
+
+    #[test]
+    fn test_quote_into_slice() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [&b""[..], b"foobar", b"foo bar", b"it's", &bytes] {
+            let expected = Fish::quote_vec(sample);
+            let mut buf = vec![0u8; expected.len() + 1];
+            let len = Fish::quote_into_slice(sample, &mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let len = Fish::quote_into_slice(sample, &mut buf[..expected.len()]).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let err = Fish::quote_into_slice(sample, &mut buf[..expected.len() - 1]).unwrap_err();
+            assert_eq!(err.needed(), expected.len());
+            assert_eq!(err.available(), expected.len() - 1);
+        }
+    }
+
     #[test]
     fn test_escape_into_plain() {
         let mut buffer = Vec::new();
@@ -247,6 +392,418 @@ mod fish_impl {
     }
 }
 
+// -- Fish::display ----------------------------------------------------------
+
+mod fish_display {
+    use std::fmt::{self, Write};
+
+    use shell_quote::Fish;
+
+    use super::resources;
+
+    fn samples() -> Vec<String> {
+        let ascii: String = (0..=0x7Fu8).map(char::from).collect();
+        let mut samples: Vec<String> = ["", "foobar", "foo bar", "it's", "'\"'"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        samples.extend(ascii.chars().map(String::from));
+        samples.push(ascii);
+        samples.push(resources::UTF8_SAMPLE.into());
+        samples
+    }
+
+    #[test]
+    fn test_display_matches_quote_vec() {
+        for sample in samples() {
+            let displayed = Fish::display(sample.as_str()).to_string();
+            assert_eq!(displayed.as_bytes(), Fish::quote_vec(&sample), "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn test_display_bytes_matches_quote_vec() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for byte in &bytes {
+            let displayed = Fish::display(&[*byte]).to_string();
+            assert_eq!(displayed.as_bytes(), Fish::quote_vec(&[*byte]));
+        }
+        let displayed = Fish::display(&bytes).to_string();
+        assert_eq!(displayed.as_bytes(), Fish::quote_vec(&bytes));
+    }
+
+    #[test]
+    fn test_debug_matches_display() {
+        for sample in samples() {
+            let quoted = Fish::display(sample.as_str());
+            assert_eq!(format!("{quoted:?}"), format!("{quoted}"));
+        }
+    }
+
+    #[test]
+    fn test_display_in_format() {
+        assert_eq!(
+            format!("cat {} -", Fish::display("foo bar")),
+            "cat foo' bar' -"
+        );
+    }
+
+    #[test]
+    fn test_quote_into_fmt() {
+        for sample in samples() {
+            let mut buf = String::from("> ");
+            Fish::quote_into_fmt(sample.as_str(), &mut buf).unwrap();
+            let mut expected = b"> ".to_vec();
+            Fish::quote_into_vec(&sample, &mut expected);
+            assert_eq!(buf.as_bytes(), expected);
+        }
+    }
+
+    #[test]
+    fn test_quote_into_fmt_passes_on_errors() {
+        struct Broken;
+        impl Write for Broken {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+        assert!(Fish::quote_into_fmt("foobar", &mut Broken).is_err());
+        assert!(Fish::quote_into_fmt("foo bar", &mut Broken).is_err());
+    }
+}
+
+// -- Fish::quote_into_writer ------------------------------------------------
+
+mod fish_writer {
+    use std::io::{self, Write};
+
+    use shell_quote::Fish;
+
+    use super::resources;
+
+    /// A writer that accepts `limit` bytes, then fails.
+    struct Limited {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let room = self.limit - self.written.len();
+            if room == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            let len = buf.len().min(room);
+            self.written.extend(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_quote_into_writer_matches_quote_vec() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let samples: [&[u8]; 6] = [
+            b"",
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ];
+        for sample in samples {
+            let mut buf = b"> ".to_vec();
+            Fish::quote_into_writer(sample, &mut buf).unwrap();
+            assert_eq!(buf, [&b"> "[..], &Fish::quote_vec(sample)].concat());
+        }
+        let mut buf = Vec::new();
+        Fish::quote_into_writer(resources::UTF8_SAMPLE, &mut buf).unwrap();
+        assert_eq!(buf, Fish::quote_vec(resources::UTF8_SAMPLE));
+    }
+
+    #[test]
+    fn test_join_into_writer_matches_join_vec() {
+        let args = ["ls", "-l", "foo bar", "", "it's"];
+        let mut buf = Vec::new();
+        Fish::join_into_writer(args, &mut buf).unwrap();
+        assert_eq!(buf, Fish::join_vec(args));
+        let mut buf = Vec::new();
+        Fish::join_into_writer(Vec::<&str>::new(), &mut buf).unwrap();
+        assert_eq!(buf, b"");
+    }
+
+    #[test]
+    fn test_quote_into_writer_passes_on_errors() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let expected = Fish::quote_vec(&bytes);
+        for limit in [0, 1, 2, expected.len() / 2, expected.len() - 1] {
+            let mut writer = Limited {
+                written: Vec::new(),
+                limit,
+            };
+            let err = Fish::quote_into_writer(&bytes, &mut writer).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+            // Everything up to the failure was written.
+            assert_eq!(writer.written, expected[..limit]);
+        }
+        let mut writer = Limited {
+            written: Vec::new(),
+            limit: 5,
+        };
+        let err = Fish::join_into_writer(["foo", "bar"], &mut writer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
+
+// -- Fish::unquote -----------------------------------------------------------
+
+mod fish_unquote {
+    use super::resources;
+    use shell_quote::{Fish, UnquoteErrorKind};
+
+    #[test]
+    fn test_unquote_segments() {
+        assert_eq!(Fish::unquote("foobar").unwrap(), b"foobar");
+        assert_eq!(Fish::unquote("''").unwrap(), b"");
+        assert_eq!(Fish::unquote("foo' bar'").unwrap(), b"foo bar");
+        assert_eq!(
+            Fish::unquote(r#"a\ b'c d'"e \"f\""\tg"#).unwrap(),
+            b"a bc de \"f\"\tg"
+        );
+    }
+
+    #[test]
+    fn test_unquote_single_quotes() {
+        assert_eq!(Fish::unquote(r"'\\ \' \n \$'").unwrap(), b"\\ ' \\n \\$");
+    }
+
+    #[test]
+    fn test_unquote_double_quotes() {
+        assert_eq!(
+            Fish::unquote(r#""\" \$ \\ \n \' \a""#).unwrap(),
+            b"\" $ \\ \\n \\' \\a"
+        );
+        assert_eq!(Fish::unquote("\"foo\\\nbar\"").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_unquote_escapes() {
+        assert_eq!(
+            Fish::unquote(r"\a\b\e\f\n\r\t\v").unwrap(),
+            b"\x07\x08\x1B\x0C\n\r\t\x0B"
+        );
+        assert_eq!(
+            Fish::unquote(r"\x41\X7F\xff\x4142").unwrap(),
+            b"A\x7F\xFFA42"
+        );
+        assert_eq!(Fish::unquote(r"\101\0\377\1018").unwrap(), b"A\0\xFFA8");
+        assert_eq!(
+            Fish::unquote(r"é\U0001F600\u41").unwrap(),
+            "é😀A".as_bytes()
+        );
+        assert_eq!(Fish::unquote(r"\cA\ca\c[\c_").unwrap(), b"\x01\x01\x1B\x1F");
+        assert_eq!(
+            Fish::unquote(r#"\ \$\\\*\?\~\%\#\(\)\{\}\[\]\<\>\^\&\|\;\"\'"#).unwrap(),
+            b" $\\*?~%#(){}[]<>^&|;\"'"
+        );
+        assert_eq!(Fish::unquote("foo\\\nbar").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_unquote_errors() {
+        let err = Fish::unquote("foo'bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedSingleQuote);
+        assert_eq!(err.position(), 3);
+        let err = Fish::unquote(r"'foo\'").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedSingleQuote);
+        let err = Fish::unquote("a\"foo").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedDoubleQuote);
+        assert_eq!(err.position(), 1);
+        let err = Fish::unquote("foo\\").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::TrailingBackslash);
+        for (input, position) in [
+            (r"\x", 0),
+            (r"a\xg", 1),
+            (r"\400", 0),
+            (r"\ud800", 0),
+            (r"\U110000", 0),
+            (r"\c1", 0),
+            (r"\c", 0),
+        ] {
+            let err = Fish::unquote(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::InvalidEscape, "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+        for input in ["$foo", "\"$foo\"", "*", "~", "{a,b}", "a?"] {
+            let err = Fish::unquote(input).unwrap_err();
+            assert!(
+                matches!(err.kind(), UnquoteErrorKind::Expansion(_)),
+                "{input:?} -> {err}"
+            );
+        }
+        let err = Fish::unquote("foo bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Metacharacter(b' '));
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_each_byte() {
+        for ch in u8::MIN..=u8::MAX {
+            let quoted = Fish::quote_vec(&[ch]);
+            assert_eq!(Fish::unquote(&quoted).unwrap(), [ch], "{quoted:?}");
+        }
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_all_bytes() {
+        let input: Vec<u8> = (u8::MIN..=u8::MAX).collect();
+        let quoted = Fish::quote_vec(&input);
+        assert_eq!(Fish::unquote(&quoted).unwrap(), input);
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_utf8() {
+        let quoted = Fish::quote_vec(resources::UTF8_SAMPLE);
+        assert_eq!(
+            Fish::unquote(&quoted).unwrap(),
+            resources::UTF8_SAMPLE.as_bytes()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unquote_agrees_with_fish() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let words = [
+            r#"a\ b'c d'"e \"f\""\tg"#,
+            r"'\\ \' \n \$'",
+            r#""\" \$ \\ \n \' \a""#,
+            r"\a\b\e\f\n\r\t\v",
+            r"\x41\X7F\xff\x4142",
+            r"\101\377\1018",
+            r"é\U0001F600\u41",
+            r"\cA\ca\c[\c_",
+        ];
+        for bin in find_bins("fish") {
+            for word in words {
+                let script = OsString::from_vec(format!("printf %s {word}").into_bytes());
+                let output = invoke_shell(&bin, &script).unwrap();
+                assert_eq!(Fish::unquote(word).unwrap(), output.stdout, "{word:?}");
+            }
+        }
+    }
+}
+
+// -- Fish::split -----------------------------------------------------------
+
+mod fish_split {
+    use shell_quote::{Fish, UnquoteErrorKind};
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(Fish::split("").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Fish::split(" \t ").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Fish::split("foo").unwrap(), [b"foo"]);
+        assert_eq!(
+            Fish::split("  foo\tbar  baz ").unwrap(),
+            [&b"foo"[..], b"bar", b"baz"]
+        );
+        assert_eq!(Fish::split("'' foo ''").unwrap(), [&b""[..], b"foo", b""]);
+        assert_eq!(
+            Fish::split(r#"a' b'"c d"\ e x\ty"#).unwrap(),
+            [&b"a bc d e"[..], b"x\ty"]
+        );
+    }
+
+    #[test]
+    fn test_split_comments_and_newlines() {
+        assert_eq!(Fish::split("foo # bar baz").unwrap(), [b"foo"]);
+        assert_eq!(Fish::split("# bar baz").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Fish::split("foo#bar").unwrap(), [b"foo#bar"]);
+        assert_eq!(Fish::split("foo\n").unwrap(), [b"foo"]);
+        assert_eq!(Fish::split("foo # bar\n  \n# baz\n").unwrap(), [b"foo"]);
+        assert_eq!(Fish::split("foo \\\n bar").unwrap(), [b"foo", b"bar"]);
+        let err = Fish::split("foo\nbar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'\n'));
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn test_split_operators() {
+        for (input, op, position) in [
+            ("ls | wc", b'|', 3),
+            ("a; b", b';', 1),
+            ("a && b", b'&', 2),
+            ("a &", b'&', 2),
+            ("a > b", b'>', 2),
+            ("a 2>b", b'>', 3),
+            ("a <b", b'<', 2),
+            ("(a)", b'(', 0),
+        ] {
+            let err = Fish::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Operator(op), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_expansions() {
+        for (input, ch, position) in [
+            ("echo $HOME", b'$', 5),
+            ("ls *.txt", b'*', 3),
+            ("cd ~", b'~', 3),
+        ] {
+            let err = Fish::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Expansion(ch), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_roundtrip() {
+        let args: Vec<Vec<u8>> = vec![
+            b"rsync".to_vec(),
+            b"".to_vec(),
+            b"my dir/".to_vec(),
+            b"#not-a-comment".to_vec(),
+            b"a | b; c && d > e".to_vec(),
+            (u8::MIN..=u8::MAX).collect(),
+            "Hello 👋".into(),
+        ];
+        assert_eq!(Fish::split(&Fish::join_vec(&args)).unwrap(), args);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_agrees_with_fish() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let lines = [
+            r#"a' b'"c d"\ e x\ty"#,
+            "foo # bar baz",
+            "foo#bar \\\n baz",
+            "'' x ''",
+        ];
+        for bin in find_bins("fish") {
+            for line in lines {
+                let script = format!("printf '%s\\0' {line}");
+                let output = invoke_shell(&bin, &OsString::from_vec(script.into_bytes())).unwrap();
+                let mut expected: Vec<_> = output
+                    .stdout
+                    .split(|&ch| ch == 0)
+                    .map(<[u8]>::to_vec)
+                    .collect();
+                expected.pop(); // Trailing terminator.
+                assert_eq!(Fish::split(line).unwrap(), expected, "{line:?}");
+            }
+        }
+    }
+}
+
 // -- QuoteExt ----------------------------------------------------------------
 
 mod fish_quote_ext {
@@ -260,6 +817,14 @@ mod fish_quote_ext {
         assert_eq!(string, "Hello, World,' Bob, !@#$%^&*(){}[]'");
     }
 
+    #[test]
+    fn test_vec_push_quoted_join_with_fish() {
+        let mut buffer = Vec::from(b"exec ");
+        buffer.push_quoted_join(Fish, ["foo", "foo bar", ""]);
+        let string = String::from_utf8(buffer).unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_os_string_push_quoted_with_fish() {
@@ -271,6 +836,25 @@ mod fish_quote_ext {
         assert_eq!(string, "Hello, World,' Bob, !@#$%^&*(){}[]'");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_os_string_push_quoted_join_with_fish() {
+        use std::ffi::OsString;
+
+        let mut buffer: OsString = "exec ".into();
+        buffer.push_quoted_join(Fish, ["foo", "foo bar", ""]);
+        let string = buffer.into_string().unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
+    #[cfg(feature = "bstr")]
+    #[test]
+    fn test_bstring_push_quoted_join_with_fish() {
+        let mut string: bstr::BString = "exec ".into();
+        string.push_quoted_join(Fish, ["foo", "foo bar", ""]);
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
     #[cfg(feature = "bstr")]
     #[test]
     fn test_bstring_push_quoted_with_fish() {
@@ -0,0 +1,77 @@
+#![cfg(any(feature = "bash", feature = "fish", feature = "sh"))]
+
+//! Quoting that needs neither `std` nor `alloc` in the library, i.e. into a
+//! slice or a [`fmt::Write`]. These tests run whichever features are enabled.
+
+use std::fmt::{self, Write};
+
+/// A [`fmt::Write`] into a fixed-size buffer, as might be found on a target
+/// without an allocator.
+struct Fixed {
+    buf: [u8; 64],
+    len: usize,
+}
+
+impl Fixed {
+    fn new() -> Self {
+        Self {
+            buf: [0; 64],
+            len: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Write for Fixed {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dest = self.buf.get_mut(self.len..end).ok_or(fmt::Error)?;
+        dest.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+macro_rules! check {
+    ($shell:ident, $input:expr, $expected:expr) => {{
+        let expected: &[u8] = $expected;
+        assert_eq!(shell_quote::$shell::quoted_len($input), expected.len());
+        let mut buf = [0u8; 64];
+        let len = shell_quote::$shell::quote_into_slice($input, &mut buf).unwrap();
+        assert_eq!(&buf[..len], expected);
+        let mut fixed = Fixed::new();
+        write!(fixed, "{}", shell_quote::$shell::display($input)).unwrap();
+        assert_eq!(fixed.as_bytes(), expected);
+    }};
+}
+
+#[cfg(feature = "sh")]
+#[test]
+fn test_sh() {
+    check!(Sh, "", b"''");
+    check!(Sh, "foobar", b"foobar");
+    check!(Sh, "foo bar", b"foo' bar'");
+    check!(Sh, "it's", b"it\\'s");
+}
+
+#[cfg(feature = "bash")]
+#[test]
+fn test_bash() {
+    check!(Bash, "", b"''");
+    check!(Bash, "foobar", b"foobar");
+    check!(Bash, "foo bar", b"$'foo bar'");
+    check!(Bash, "it's", b"$'it\\'s'");
+    check!(Bash, &b"\xFF"[..], b"$'\\xFF'");
+}
+
+#[cfg(feature = "fish")]
+#[test]
+fn test_fish() {
+    check!(Fish, "", b"''");
+    check!(Fish, "foobar", b"foobar");
+    check!(Fish, "foo bar", b"foo' bar'");
+    check!(Fish, &b"\xFF"[..], b"\\XFF");
+}
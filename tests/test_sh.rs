@@ -1,4 +1,4 @@
-#![cfg(all(unix, feature = "sh"))]
+#![cfg(all(unix, feature = "sh", feature = "std"))]
 
 mod resources;
 mod util;
@@ -35,6 +35,7 @@ pub(crate) fn invoke_zsh_as_sh(bin: &Path, script: &OsStr) -> io::Result<Output>
 // -- impl Sh -----------------------------------------------------------------
 
 mod sh_impl {
+    use std::borrow::Cow;
     use std::ffi::{OsStr, OsString};
     use std::{io::Result, path::Path, process::Output};
 
@@ -107,6 +108,132 @@ mod sh_impl {
         assert_eq!(Sh::quote_vec("\x1B"), b"'\x1B'");
     }
 
+    #[test]
+    fn test_join() {
+        assert_eq!(Sh::join_vec(["foo", "foo bar"]), b"foo foo' bar'");
+        assert_eq!(Sh::join_vec([""]), b"''");
+        assert_eq!(Sh::join_vec(["", ""]), b"'' ''");
+        assert_eq!(Sh::join_vec(Vec::<&[u8]>::new()), b"");
+        let mut buffer = b"exec".to_vec();
+        Sh::join_into_vec(Vec::<&str>::new(), &mut buffer);
+        assert_eq!(buffer, b"exec");
+        buffer.push(b' ');
+        Sh::join_into_vec(["foo", "foo bar"], &mut buffer);
+        assert_eq!(buffer, b"exec foo foo' bar'");
+    }
+
+    #[test]
+    fn test_quote_cow() {
+        let inert = "foo/bar-1.2";
+        match Sh::quote_cow(inert) {
+            Cow::Borrowed(quoted) => assert!(std::ptr::eq(quoted, inert.as_bytes())),
+            Cow::Owned(_) => panic!("inert input was copied"),
+        }
+        assert!(matches!(Sh::quote_cow(""), Cow::Borrowed(b"''")));
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foo bar"[..],
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Sh::quote_cow(sample);
+            assert!(matches!(quoted, Cow::Owned(_)));
+            assert_eq!(quoted, Sh::quote_vec(sample));
+        }
+        assert_eq!(
+            Sh::quote_cow(resources::UTF8_SAMPLE),
+            Sh::quote_vec(resources::UTF8_SAMPLE)
+        );
+    }
+
+    #[test]
+    fn test_needs_quoting() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo/bar-1.2",
+            b"foo bar",
+            b"FOO=bar",
+            "café".as_bytes(),
+            b"caf\xE9",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(
+                Sh::needs_quoting(sample),
+                Sh::quote_vec(sample) != sample,
+                "{sample:?}"
+            );
+        }
+        assert!(!Sh::needs_quoting("café"));
+    }
+
+    #[test]
+    fn test_explain() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b"foobar"[..],
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let why = Sh::explain(sample);
+            assert_eq!(!why.is_empty(), Sh::needs_quoting(sample), "{sample:?}");
+            for explanation in why {
+                let ch = shell_quote::Char::from_byte(sample[explanation.position()]);
+                assert_eq!(explanation.char(), ch);
+                assert!(!ch.is_inert());
+            }
+        }
+        assert!(Sh::explain("").is_empty());
+        let why = Sh::explain("a b|c");
+        let positions: Vec<_> = why.iter().map(|why| why.position()).collect();
+        assert_eq!(positions, [1, 3]);
+        assert_eq!(why[0].to_string(), "` ` at 1: whitespace separates words");
+        assert_eq!(
+            why[1].to_string(),
+            "`|` at 3: control operator or redirection"
+        );
+    }
+
+    #[test]
+    fn test_quoted_len() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            assert_eq!(Sh::quoted_len(sample), Sh::quote_vec(sample).len());
+        }
+        assert_eq!(
+            Sh::quoted_len(resources::UTF8_SAMPLE),
+            Sh::quote_vec(resources::UTF8_SAMPLE).len()
+        );
+    }
+
+    #[test]
+    fn test_quote_into_slice() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [&b""[..], b"foobar", b"foo bar", b"it's", &bytes] {
+            let expected = Sh::quote_vec(sample);
+            let mut buf = vec![0u8; expected.len() + 1];
+            let len = Sh::quote_into_slice(sample, &mut buf).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let len = Sh::quote_into_slice(sample, &mut buf[..expected.len()]).unwrap();
+            assert_eq!(&buf[..len], expected);
+            let err = Sh::quote_into_slice(sample, &mut buf[..expected.len() - 1]).unwrap_err();
+            assert_eq!(err.needed(), expected.len());
+            assert_eq!(err.available(), expected.len() - 1);
+        }
+    }
+
     #[test]
     fn test_quote_into_plain() {
         let mut buffer = Vec::new();
@@ -203,6 +330,397 @@ mod sh_impl {
     }
 }
 
+// -- Sh::display ------------------------------------------------------------
+
+mod sh_display {
+    use std::fmt::{self, Write};
+
+    use shell_quote::Sh;
+
+    use super::resources;
+
+    fn samples() -> Vec<String> {
+        let ascii: String = (0..=0x7Fu8).map(char::from).collect();
+        let mut samples: Vec<String> = ["", "foobar", "foo bar", "it's", "'\"'"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        samples.extend(ascii.chars().map(String::from));
+        samples.push(ascii);
+        samples.push(resources::UTF8_SAMPLE.into());
+        samples
+    }
+
+    #[test]
+    fn test_display_matches_quote_vec() {
+        for sample in samples() {
+            let displayed = Sh::display(sample.as_str()).to_string();
+            assert_eq!(displayed.as_bytes(), Sh::quote_vec(&sample), "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn test_debug_matches_display() {
+        for sample in samples() {
+            let quoted = Sh::display(sample.as_str());
+            assert_eq!(format!("{quoted:?}"), format!("{quoted}"));
+        }
+    }
+
+    #[test]
+    fn test_display_in_format() {
+        assert_eq!(
+            format!("cat {} -", Sh::display("foo bar")),
+            "cat foo' bar' -"
+        );
+    }
+
+    #[test]
+    fn test_quote_into_fmt() {
+        for sample in samples() {
+            let mut buf = String::from("> ");
+            Sh::quote_into_fmt(sample.as_str(), &mut buf).unwrap();
+            let mut expected = b"> ".to_vec();
+            Sh::quote_into_vec(&sample, &mut expected);
+            assert_eq!(buf.as_bytes(), expected);
+        }
+    }
+
+    #[test]
+    fn test_quote_into_fmt_passes_on_errors() {
+        struct Broken;
+        impl Write for Broken {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+        assert!(Sh::quote_into_fmt("foobar", &mut Broken).is_err());
+        assert!(Sh::quote_into_fmt("foo bar", &mut Broken).is_err());
+    }
+}
+
+// -- Sh::quote_into_writer --------------------------------------------------
+
+mod sh_writer {
+    use std::io::{self, Write};
+
+    use shell_quote::Sh;
+
+    use super::resources;
+
+    /// A writer that accepts `limit` bytes, then fails.
+    struct Limited {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let room = self.limit - self.written.len();
+            if room == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            let len = buf.len().min(room);
+            self.written.extend(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_quote_into_writer_matches_quote_vec() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let samples: [&[u8]; 6] = [
+            b"",
+            b"foobar",
+            b"foo bar",
+            b"it's",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ];
+        for sample in samples {
+            let mut buf = b"> ".to_vec();
+            Sh::quote_into_writer(sample, &mut buf).unwrap();
+            assert_eq!(buf, [&b"> "[..], &Sh::quote_vec(sample)].concat());
+        }
+        let mut buf = Vec::new();
+        Sh::quote_into_writer(resources::UTF8_SAMPLE, &mut buf).unwrap();
+        assert_eq!(buf, Sh::quote_vec(resources::UTF8_SAMPLE));
+    }
+
+    #[test]
+    fn test_join_into_writer_matches_join_vec() {
+        let args = ["ls", "-l", "foo bar", "", "it's"];
+        let mut buf = Vec::new();
+        Sh::join_into_writer(args, &mut buf).unwrap();
+        assert_eq!(buf, Sh::join_vec(args));
+        let mut buf = Vec::new();
+        Sh::join_into_writer(Vec::<&str>::new(), &mut buf).unwrap();
+        assert_eq!(buf, b"");
+    }
+
+    #[test]
+    fn test_quote_into_writer_passes_on_errors() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let expected = Sh::quote_vec(&bytes);
+        for limit in [0, 1, 2, expected.len() / 2, expected.len() - 1] {
+            let mut writer = Limited {
+                written: Vec::new(),
+                limit,
+            };
+            let err = Sh::quote_into_writer(&bytes, &mut writer).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+            // Everything up to the failure was written.
+            assert_eq!(writer.written, expected[..limit]);
+        }
+        let mut writer = Limited {
+            written: Vec::new(),
+            limit: 5,
+        };
+        let err = Sh::join_into_writer(["foo", "bar"], &mut writer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
+
+// -- Sh::unquote -------------------------------------------------------------
+
+mod sh_unquote {
+    use super::resources;
+    use shell_quote::{Sh, UnquoteErrorKind};
+
+    #[test]
+    fn test_unquote_plain() {
+        assert_eq!(Sh::unquote("foobar").unwrap(), b"foobar");
+        assert_eq!(Sh::unquote("--foo=bar%1").unwrap(), b"--foo=bar%1");
+        assert_eq!(Sh::unquote("").unwrap(), b"");
+        assert_eq!(Sh::unquote("''").unwrap(), b"");
+        assert_eq!(Sh::unquote("\"\"").unwrap(), b"");
+    }
+
+    #[test]
+    fn test_unquote_single_quotes() {
+        assert_eq!(Sh::unquote("foo' bar'").unwrap(), b"foo bar");
+        assert_eq!(Sh::unquote("'$HOME \\ \"'").unwrap(), b"$HOME \\ \"");
+        assert_eq!(Sh::unquote(r"'it'\''s'").unwrap(), b"it's");
+    }
+
+    #[test]
+    fn test_unquote_double_quotes() {
+        assert_eq!(Sh::unquote(r#""foo bar""#).unwrap(), b"foo bar");
+        assert_eq!(Sh::unquote(r#""\$\`\"\\""#).unwrap(), b"$`\"\\");
+        // Backslash is literal before anything else.
+        assert_eq!(Sh::unquote(r#""\a\'""#).unwrap(), b"\\a\\'");
+        // Line continuation.
+        assert_eq!(Sh::unquote("\"foo\\\nbar\"").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_unquote_backslash() {
+        assert_eq!(Sh::unquote(r"foo\ bar\\").unwrap(), b"foo bar\\");
+        assert_eq!(Sh::unquote(r"\'\$\*").unwrap(), b"'$*");
+        assert_eq!(Sh::unquote("foo\\\nbar").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_unquote_errors() {
+        let err = Sh::unquote("foo'bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedSingleQuote);
+        assert_eq!(err.position(), 3);
+        let err = Sh::unquote("a\"bc\\\"").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::UnterminatedDoubleQuote);
+        assert_eq!(err.position(), 1);
+        let err = Sh::unquote("foo\\").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::TrailingBackslash);
+        assert_eq!(err.position(), 3);
+        let err = Sh::unquote("foo bar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Metacharacter(b' '));
+        assert_eq!(err.position(), 3);
+        let err = Sh::unquote("\"$HOME\"").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Expansion(b'$'));
+        assert_eq!(err.position(), 1);
+        for input in ["a|b", "a;b", "a&b", "a>b", "(a)"] {
+            let err = Sh::unquote(input).unwrap_err();
+            assert!(
+                matches!(err.kind(), UnquoteErrorKind::Operator(_)),
+                "{input:?} -> {err}"
+            );
+        }
+        for input in ["#", "#a", "a^b", "a]"] {
+            let err = Sh::unquote(input).unwrap_err();
+            assert!(
+                matches!(err.kind(), UnquoteErrorKind::Metacharacter(_)),
+                "{input:?} -> {err}"
+            );
+        }
+        for input in [
+            "$a", "*", "a?", "[a]", "~", "a=~", "a:~/bin", "`a`", "{a,b}", "!a",
+        ] {
+            let err = Sh::unquote(input).unwrap_err();
+            assert!(
+                matches!(err.kind(), UnquoteErrorKind::Expansion(_)),
+                "{input:?} -> {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unquote_tilde_within_word() {
+        // Only a `~` at the start of a word, or after `=` or `:`, is expanded.
+        assert_eq!(Sh::unquote("a~b").unwrap(), b"a~b");
+        assert_eq!(Sh::unquote("'a'~").unwrap(), b"a~");
+        assert_eq!(Sh::split("x a~b").unwrap(), [&b"x"[..], b"a~b"]);
+        // Nor is one after a blank, `=`, or `:` that is quoted or escaped.
+        assert_eq!(Sh::unquote(r"\ ~").unwrap(), b" ~");
+        assert_eq!(Sh::unquote("' '~").unwrap(), b" ~");
+        assert_eq!(Sh::unquote(r"a\=~").unwrap(), b"a=~");
+        assert_eq!(Sh::unquote("a':'~").unwrap(), b"a:~");
+        assert_eq!(Sh::split(r"a\ ~ b").unwrap(), [&b"a ~"[..], b"b"]);
+        // But it is at the start of every word.
+        let err = Sh::split("a ~").unwrap_err();
+        assert_eq!(err.position(), 2);
+    }
+
+    #[test]
+    fn test_unquote_error_leaves_buffer_alone() {
+        let mut buf = b"hello".to_vec();
+        assert!(Sh::unquote_into_vec("foo'bar", &mut buf).is_err());
+        assert_eq!(buf, b"hello");
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_each_byte() {
+        for ch in u8::MIN..=u8::MAX {
+            let quoted = Sh::quote_vec(&[ch]);
+            assert_eq!(Sh::unquote(&quoted).unwrap(), [ch], "{quoted:?}");
+        }
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_all_bytes() {
+        let input: Vec<u8> = (u8::MIN..=u8::MAX).collect();
+        let quoted = Sh::quote_vec(&input);
+        assert_eq!(Sh::unquote(&quoted).unwrap(), input);
+    }
+
+    #[test]
+    fn test_unquote_roundtrip_utf8() {
+        let quoted = Sh::quote_vec(resources::UTF8_SAMPLE);
+        assert_eq!(
+            Sh::unquote(&quoted).unwrap(),
+            resources::UTF8_SAMPLE.as_bytes()
+        );
+    }
+}
+
+// -- Sh::split -------------------------------------------------------------
+
+mod sh_split {
+    use shell_quote::{Sh, UnquoteErrorKind};
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(Sh::split("").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Sh::split(" \t ").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Sh::split("foo").unwrap(), [b"foo"]);
+        assert_eq!(
+            Sh::split("  foo\tbar  baz ").unwrap(),
+            [&b"foo"[..], b"bar", b"baz"]
+        );
+        assert_eq!(Sh::split("'' foo ''").unwrap(), [&b""[..], b"foo", b""]);
+        assert_eq!(
+            Sh::split(r#"a' b'"c d"\ e it\'s"#).unwrap(),
+            [&b"a bc d e"[..], b"it's"]
+        );
+    }
+
+    #[test]
+    fn test_split_comments_and_newlines() {
+        assert_eq!(Sh::split("foo # bar baz").unwrap(), [b"foo"]);
+        assert_eq!(Sh::split("# bar baz").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(Sh::split("foo#bar").unwrap(), [b"foo#bar"]);
+        assert_eq!(Sh::split("foo\n").unwrap(), [b"foo"]);
+        assert_eq!(Sh::split("foo # bar\n  \n# baz\n").unwrap(), [b"foo"]);
+        assert_eq!(Sh::split("foo \\\n bar").unwrap(), [b"foo", b"bar"]);
+        let err = Sh::split("foo\nbar").unwrap_err();
+        assert_eq!(err.kind(), UnquoteErrorKind::Operator(b'\n'));
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn test_split_operators() {
+        for (input, op, position) in [
+            ("ls | wc", b'|', 3),
+            ("a; b", b';', 1),
+            ("a && b", b'&', 2),
+            ("a &", b'&', 2),
+            ("a > b", b'>', 2),
+            ("a 2>b", b'>', 3),
+            ("a <b", b'<', 2),
+            ("(a)", b'(', 0),
+        ] {
+            let err = Sh::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Operator(op), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_expansions() {
+        for (input, ch, position) in [
+            ("echo $HOME", b'$', 5),
+            ("ls *.txt", b'*', 3),
+            ("cd ~", b'~', 3),
+        ] {
+            let err = Sh::split(input).unwrap_err();
+            assert_eq!(err.kind(), UnquoteErrorKind::Expansion(ch), "{input:?}");
+            assert_eq!(err.position(), position, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_split_roundtrip() {
+        let args: Vec<Vec<u8>> = vec![
+            b"rsync".to_vec(),
+            b"".to_vec(),
+            b"my dir/".to_vec(),
+            b"#not-a-comment".to_vec(),
+            b"a | b; c && d > e".to_vec(),
+            (u8::MIN..=u8::MAX).collect(),
+            "Hello 👋".into(),
+        ];
+        assert_eq!(Sh::split(&Sh::join_vec(&args)).unwrap(), args);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_agrees_with_sh() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let lines = [
+            r#"a' b'"c d"\ e it\'s"#,
+            "foo # bar baz",
+            "foo#bar \\\n baz",
+            "'' x ''",
+        ];
+        for bin in find_bins("sh") {
+            for line in lines {
+                let script = format!("printf '%s\\0' {line}");
+                let output = invoke_shell(&bin, &OsString::from_vec(script.into_bytes())).unwrap();
+                let mut expected: Vec<_> = output
+                    .stdout
+                    .split(|&ch| ch == 0)
+                    .map(<[u8]>::to_vec)
+                    .collect();
+                expected.pop(); // Trailing terminator.
+                assert_eq!(Sh::split(line).unwrap(), expected, "{line:?}");
+            }
+        }
+    }
+}
+
 // -- QuoteExt ----------------------------------------------------------------
 
 mod sh_quote_ext {
@@ -218,6 +736,14 @@ mod sh_quote_ext {
         assert_eq!(string, "Hello, World,' Bob, !@#$%^&*(){}[]'");
     }
 
+    #[test]
+    fn test_vec_push_quoted_join() {
+        let mut buffer = Vec::from(b"exec ");
+        buffer.push_quoted_join(Sh, ["foo", "foo bar", ""]);
+        let string = String::from_utf8(buffer).unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_os_string_push_quoted() {
@@ -227,6 +753,23 @@ mod sh_quote_ext {
         assert_eq!(string, "Hello, World,' Bob, !@#$%^&*(){}[]'");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_os_string_push_quoted_join() {
+        let mut buffer: OsString = "exec ".into();
+        buffer.push_quoted_join(Sh, ["foo", "foo bar", ""]);
+        let string = buffer.into_string().unwrap(); // -> test failures are more readable.
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
+    #[cfg(feature = "bstr")]
+    #[test]
+    fn test_bstring_push_quoted_join() {
+        let mut string: bstr::BString = "exec ".into();
+        string.push_quoted_join(Sh, ["foo", "foo bar", ""]);
+        assert_eq!(string, "exec foo foo' bar' ''");
+    }
+
     #[cfg(feature = "bstr")]
     #[test]
     fn test_bstring_push_quoted() {
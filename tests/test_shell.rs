@@ -0,0 +1,118 @@
+#![cfg(all(
+    unix,
+    feature = "sh",
+    feature = "bash",
+    feature = "fish",
+    feature = "std"
+))]
+
+use shell_quote::{Bash, Fish, Sh, Shell, Zsh};
+use test_case::test_case;
+
+#[test_case("sh", Shell::Sh)]
+#[test_case("ash", Shell::Sh)]
+#[test_case("ksh", Shell::Sh)]
+#[test_case("mksh", Shell::Sh)]
+#[test_case("dash", Shell::Dash)]
+#[test_case("bash", Shell::Bash)]
+#[test_case("zsh", Shell::Zsh)]
+#[test_case("fish", Shell::Fish)]
+#[test_case("/bin/sh", Shell::Sh)]
+#[test_case("/usr/local/bin/bash", Shell::Bash)]
+#[test_case("/opt/homebrew/bin/fish", Shell::Fish)]
+#[test_case("-zsh", Shell::Zsh; "login shell")]
+#[test_case("Bash", Shell::Bash; "mixed case")]
+#[test_case(r"C:\Program Files\Git\bin\bash.exe", Shell::Bash; "windows path")]
+fn test_parse(name: &str, expected: Shell) {
+    assert_eq!(name.parse::<Shell>(), Ok(expected));
+}
+
+#[test_case(""; "empty")]
+#[test_case("tcsh")]
+#[test_case("bash5")]
+#[test_case("/bin/")]
+#[test_case("/usr/bin/env bash"; "with arguments")]
+#[test_case("éabc"; "non-ascii before suffix")]
+#[test_case("/opt/ñbsh"; "non-ascii path")]
+#[test_case("bashé.exe"; "non-ascii with suffix")]
+fn test_parse_unrecognised(name: &str) {
+    let err = name.parse::<Shell>().unwrap_err();
+    assert_eq!(err.to_string(), "unrecognised shell");
+}
+
+#[test]
+fn test_name_roundtrip() {
+    for shell in [Shell::Sh, Shell::Dash, Shell::Bash, Shell::Zsh, Shell::Fish] {
+        assert_eq!(shell.to_string().parse::<Shell>(), Ok(shell));
+        assert_eq!(shell.to_string(), shell.name());
+    }
+}
+
+#[test]
+fn test_quote_dispatches() {
+    let bytes: Vec<u8> = (0..=u8::MAX).collect();
+    for sample in [&b""[..], b"foobar", b"foo bar", b"it's", &bytes] {
+        assert_eq!(Shell::Sh.quote_vec(sample), Sh::quote_vec(sample));
+        assert_eq!(Shell::Dash.quote_vec(sample), Sh::quote_vec(sample));
+        assert_eq!(Shell::Bash.quote_vec(sample), Bash::quote_vec(sample));
+        assert_eq!(Shell::Zsh.quote_vec(sample), Zsh::quote_vec(sample));
+        assert_eq!(Shell::Fish.quote_vec(sample), Fish::quote_vec(sample));
+    }
+}
+
+#[test]
+fn test_quote_into_other_types() {
+    use shell_quote::QuoteInto;
+    use std::ffi::OsString;
+    let mut os_string = OsString::from("ls ");
+    Shell::Fish.quote_into_os_string("foo bar", &mut os_string);
+    let mut expected = OsString::from("ls ");
+    Fish::quote_into("foo bar", &mut expected);
+    assert_eq!(os_string, expected);
+    #[cfg(feature = "bstr")]
+    {
+        let mut bstring = bstr::BString::from("ls ");
+        Shell::Sh.quote_into_bstring(b"foo\xFFbar", &mut bstring);
+        let mut expected = bstr::BString::from("ls ");
+        Sh::quote_into(b"foo\xFFbar", &mut expected);
+        assert_eq!(bstring, expected);
+    }
+}
+
+#[test]
+fn test_join_dispatches() {
+    let args = ["echo", "foo bar", "", "it's"];
+    assert_eq!(Shell::Sh.join_vec(args), Sh::join_vec(args));
+    assert_eq!(Shell::Bash.join_vec(args), Bash::join_vec(args));
+    assert_eq!(Shell::Fish.join_vec(args), Fish::join_vec(args));
+    let mut buf = Vec::new();
+    Shell::Bash.join_into_writer(args, &mut buf).unwrap();
+    assert_eq!(buf, Bash::join_vec(args));
+}
+
+#[test]
+fn test_quoted_len_and_quote_into_slice() {
+    for shell in [Shell::Sh, Shell::Bash, Shell::Fish] {
+        let expected = shell.quote_vec("it's here");
+        assert_eq!(shell.quoted_len("it's here"), expected.len());
+        let mut buf = [0u8; 32];
+        let len = shell.quote_into_slice("it's here", &mut buf).unwrap();
+        assert_eq!(&buf[..len], expected);
+        let err = shell
+            .quote_into_slice("it's here", &mut buf[..3])
+            .unwrap_err();
+        assert_eq!(err.needed(), expected.len());
+    }
+}
+
+/// All in one test because the environment is shared between threads.
+#[test]
+fn test_from_env() {
+    std::env::set_var("SHELL", "/usr/bin/fish");
+    assert_eq!(Shell::from_env(), Shell::Fish);
+    std::env::set_var("SHELL", "/bin/tcsh");
+    assert_eq!(Shell::from_env(), Shell::Sh);
+    std::env::remove_var("SHELL");
+    assert_eq!(Shell::from_env(), Shell::Sh);
+    assert_eq!(Shell::default(), Shell::Sh);
+}
@@ -0,0 +1,168 @@
+#![cfg(all(
+    unix,
+    feature = "sh",
+    feature = "bash",
+    feature = "fish",
+    feature = "std"
+))]
+
+mod resources;
+mod util;
+
+use shell_quote::{Bash, Fish, Quoter, Sh, Style};
+
+/// Every combination of style.
+const STYLES: [Style; 4] = [
+    Style {
+        prefer_plain_single_quotes: false,
+        whole_word: false,
+    },
+    Style {
+        prefer_plain_single_quotes: false,
+        whole_word: true,
+    },
+    Style {
+        prefer_plain_single_quotes: true,
+        whole_word: false,
+    },
+    Style {
+        prefer_plain_single_quotes: true,
+        whole_word: true,
+    },
+];
+
+fn samples() -> Vec<Vec<u8>> {
+    let mut samples: Vec<Vec<u8>> = (0..=u8::MAX).map(|ch| vec![ch]).collect();
+    samples.push((0..=u8::MAX).collect());
+    samples.push(resources::UTF8_SAMPLE.as_bytes().to_vec());
+    for sample in [
+        "", "foo", "foo bar", "it's", "'", "''", "a\tb c", "café", "x=\\y",
+    ] {
+        samples.push(sample.as_bytes().to_vec());
+    }
+    samples
+}
+
+// -- Default styles ----------------------------------------------------------
+
+#[test]
+fn test_default_style_is_unchanged() {
+    let sh = Sh::with_style(Sh::STYLE);
+    let bash = Bash::with_style(Bash::STYLE);
+    let fish = Fish::with_style(Fish::STYLE);
+    for sample in samples() {
+        assert_eq!(sh.quote_vec(&sample), Sh::quote_vec(&sample));
+        assert_eq!(bash.quote_vec(&sample), Bash::quote_vec(&sample));
+        assert_eq!(fish.quote_vec(&sample), Fish::quote_vec(&sample));
+    }
+    // Text too, where Bash and Fish write UTF-8 as it is.
+    assert_eq!(
+        bash.quote_vec(resources::UTF8_SAMPLE),
+        Bash::quote_vec(resources::UTF8_SAMPLE)
+    );
+    assert_eq!(
+        fish.quote_vec(resources::UTF8_SAMPLE),
+        Fish::quote_vec(resources::UTF8_SAMPLE)
+    );
+}
+
+// -- Bash --------------------------------------------------------------------
+
+#[test]
+fn test_bash_styles() {
+    let [minimal, whole, plain_minimal, plain_whole] = STYLES.map(Bash::with_style);
+    assert_eq!(minimal.quote_vec("foo bar"), b"foo$' bar'");
+    assert_eq!(whole.quote_vec("foo bar"), b"$'foo bar'");
+    assert_eq!(plain_minimal.quote_vec("foo bar"), b"foo' bar'");
+    assert_eq!(plain_whole.quote_vec("foo bar"), b"'foo bar'");
+    assert_eq!(plain_whole.quote_vec("it's"), b"'it'\\''s'");
+    assert_eq!(plain_whole.quote_vec("'quoted'"), b"\\''quoted'\\'");
+    // Control characters need ANSI-C quoting.
+    assert_eq!(plain_whole.quote_vec("foo\tbar"), b"$'foo\\tbar'");
+    assert_eq!(plain_minimal.quote_vec("foo\tbar"), b"foo$'\\tbar'");
+    // Strings that need no quoting, or are empty, are the same in any style.
+    for bash in [minimal, whole, plain_minimal, plain_whole] {
+        assert_eq!(bash.quote_vec("foo"), b"foo");
+        assert_eq!(bash.quote_vec(""), b"''");
+        assert_eq!(bash.join_vec(["a", "", "b"]), b"a '' b");
+    }
+}
+
+#[test]
+fn test_styled_is_a_quoter() {
+    let quoter: Box<dyn Quoter> = Box::new(Bash::with_style(Style {
+        prefer_plain_single_quotes: true,
+        ..Bash::STYLE
+    }));
+    assert_eq!(quoter.quote_vec("foo bar".into()), b"'foo bar'");
+}
+
+// -- Sh & Fish ---------------------------------------------------------------
+
+#[test]
+fn test_sh_styles() {
+    let [minimal, whole, plain_minimal, plain_whole] = STYLES.map(Sh::with_style);
+    for sh in [minimal, plain_minimal] {
+        assert_eq!(sh.quote_vec("it's here"), b"it\\'s' here'");
+    }
+    for sh in [whole, plain_whole] {
+        assert_eq!(sh.quote_vec("it's here"), b"'it'\\''s here'");
+        assert_eq!(sh.quote_vec("café"), "café".as_bytes());
+        assert_eq!(sh.quote_vec("café au lait"), "'café au lait'".as_bytes());
+    }
+}
+
+#[test]
+fn test_fish_styles() {
+    let [minimal, whole, _, _] = STYLES.map(Fish::with_style);
+    assert_eq!(minimal.quote_vec("it's here"), b"it\\'s' here'");
+    assert_eq!(whole.quote_vec("it's here"), b"'it\\'s here'");
+    assert_eq!(whole.quote_vec("one\ttwo"), b"'one'\\t'two'");
+}
+
+// -- Round trips -------------------------------------------------------------
+
+mod roundtrip {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    use super::util::{find_bins, invoke_shell};
+    use super::{samples, STYLES};
+    use shell_quote::{Bash, Fish, Quoter, Sh};
+
+    fn check(shell: &str, quoters: &[&dyn Quoter]) {
+        for bin in find_bins(shell) {
+            for quoter in quoters {
+                // NUL cannot be passed as an argument, so is left out.
+                for sample in samples().into_iter().filter(|s| !s.contains(&0)) {
+                    let mut script = b"printf %s ".to_vec();
+                    quoter.quote_into_vec(sample.as_slice().into(), &mut script);
+                    let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                    assert_eq!(output.stdout, sample, "{bin:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_sh() {
+        let quoters = STYLES.map(Sh::with_style);
+        let quoters: Vec<&dyn Quoter> = quoters.iter().map(|q| q as &dyn Quoter).collect();
+        check("sh", &quoters);
+        check("dash", &quoters);
+    }
+
+    #[test]
+    fn test_roundtrip_bash() {
+        let quoters = STYLES.map(Bash::with_style);
+        let quoters: Vec<&dyn Quoter> = quoters.iter().map(|q| q as &dyn Quoter).collect();
+        check("bash", &quoters);
+    }
+
+    #[test]
+    fn test_roundtrip_fish() {
+        let quoters = STYLES.map(Fish::with_style);
+        let quoters: Vec<&dyn Quoter> = quoters.iter().map(|q| q as &dyn Quoter).collect();
+        check("fish", &quoters);
+    }
+}
@@ -1,4 +1,4 @@
-#![cfg(all(unix, feature = "bash", feature = "bstr"))]
+#![cfg(all(unix, feature = "bash", feature = "bstr", feature = "std"))]
 
 use bstr::{BString, ByteSlice};
 use std::{ffi::OsString, os::unix::ffi::OsStringExt};
@@ -185,3 +185,166 @@ fn test_quote_ref_ext_str() {
     let quoted: BString = source.quoted(Bash);
     assert_eq!(BString::from(b"$'str!'"), quoted);
 }
+
+#[test]
+fn test_quote_join() {
+    use shell_quote::Join;
+    let args = [&b"foo"[..], b"foo bar", b""];
+    let joined: Vec<u8> = Bash::join(args);
+    assert_eq!(Vec::from(b"foo $'foo bar' ''"), joined);
+    let joined: String = Bash::join(args);
+    assert_eq!("foo $'foo bar' ''", joined);
+    let joined: OsString = Bash::join(args);
+    assert_eq!(OsString::from_vec(b"foo $'foo bar' ''".into()), joined);
+    let joined: BString = Bash::join(args);
+    assert_eq!(BString::from(b"foo $'foo bar' ''"), joined);
+    let joined: String = Bash::join(Vec::<&str>::new());
+    assert_eq!("", joined);
+}
+
+#[test]
+fn test_quote_write_ext() {
+    use shell_quote::QuoteWriteExt;
+    let mut script: Vec<u8> = b"echo ".into();
+    script.write_quoted(Bash, "foo bar").unwrap();
+    script.extend(b" > ");
+    script.write_quoted(Bash, b"/path/(to)/[output]").unwrap();
+    assert_eq!(script, b"echo $'foo bar' > $'/path/(to)/[output]'");
+    let mut script: Vec<u8> = b"exec ".into();
+    script
+        .write_quoted_join(Bash, ["ls", "foo bar", ""])
+        .unwrap();
+    assert_eq!(script, b"exec ls $'foo bar' ''");
+}
+
+#[test]
+fn test_quote_write_ext_unsized() {
+    use shell_quote::QuoteWriteExt;
+    let mut buf = Vec::new();
+    let writer: &mut dyn std::io::Write = &mut buf;
+    writer.write_quoted(Bash, "foo bar").unwrap();
+    assert_eq!(buf, b"$'foo bar'");
+}
+
+#[cfg(feature = "fish")]
+#[test]
+fn test_quoter_as_trait_object() {
+    use shell_quote::{Quoter, Shell};
+
+    /// A custom quoter, as a plugin might register.
+    struct Brackets;
+
+    impl Quoter for Brackets {
+        fn quote_into_vec(&self, s: Quotable<'_>, out: &mut Vec<u8>) {
+            out.push(b'[');
+            match s {
+                Quotable::Bytes(bytes) => out.extend(bytes),
+                Quotable::Text(text) => out.extend(text.as_bytes()),
+            }
+            out.push(b']');
+        }
+    }
+
+    let quoters: Vec<Box<dyn Quoter>> =
+        vec![Box::new(Bash), Box::new(Shell::Fish), Box::new(Brackets)];
+    let quoted: Vec<Vec<u8>> = quoters
+        .iter()
+        .map(|quoter| quoter.quote_vec("foo bar".into()))
+        .collect();
+    assert_eq!(quoted, [&b"$'foo bar'"[..], b"foo' bar'", b"[foo bar]"]);
+
+    let args = ["ls".into(), "foo bar".into(), "".into()];
+    for (quoter, expected) in quoters.iter().zip([
+        &b"ls $'foo bar' ''"[..],
+        b"ls foo' bar' ''",
+        b"[ls] [foo bar] []",
+    ]) {
+        let mut joined = Vec::new();
+        quoter.join_into_vec(&args, &mut joined);
+        assert_eq!(joined, expected);
+        let mut written = Vec::new();
+        quoter.quote_into_writer(args[1], &mut written).unwrap();
+        assert_eq!(written, quoter.quote_vec(args[1]));
+    }
+}
+
+#[test]
+fn test_quoter_bridges_from_dialect() {
+    use shell_quote::{Dialect, Quoter};
+
+    fn register<D: Dialect + 'static>(quoter: D) -> Box<dyn Quoter> {
+        Box::new(quoter)
+    }
+
+    let quoter = register(Bash);
+    assert_eq!(quoter.quote_vec(b"\x07".into()), Bash::quote_vec(b"\x07"));
+}
+
+#[cfg(all(feature = "sh", feature = "fish"))]
+#[test]
+fn test_quoter_bridge_streams_into_writer() {
+    use shell_quote::{Fish, Quoter, Sh};
+
+    /// Records each write separately.
+    #[derive(Default)]
+    struct Writes(Vec<Vec<u8>>);
+
+    impl std::io::Write for Writes {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let quoters: [&dyn Quoter; 3] = [&Sh, &Bash, &Fish];
+    for quoter in quoters {
+        let mut writes = Writes::default();
+        quoter
+            .quote_into_writer("foo bar".into(), &mut writes)
+            .unwrap();
+        // Not via a temporary, which would be written in one go.
+        assert!(writes.0.len() > 1, "{:?}", writes.0);
+        assert_eq!(writes.0.concat(), quoter.quote_vec("foo bar".into()));
+    }
+}
+
+#[test]
+fn test_first_unsafe_index_agrees_with_char() {
+    use shell_quote::{first_unsafe_index, Char};
+
+    for ch in u8::MIN..=u8::MAX {
+        let bytes = [b'a', b'b', ch, b'c'];
+        let expected = (!Char::from_byte(ch).is_inert()).then_some(2);
+        assert_eq!(first_unsafe_index(&bytes), expected, "{ch:#04x}");
+        assert_eq!(Bash::needs_quoting(&bytes), expected.is_some());
+    }
+    assert_eq!(first_unsafe_index("naïve"), Some(2));
+    assert_eq!(Char::from_char('ï'), Char::Utf8('ï'));
+    assert_eq!(Char::from_byte(b'='), Char::Printable(b'='));
+    assert_eq!(Char::from_byte(0x1B), Char::Escape);
+}
+
+#[test]
+fn test_reason_covers_every_printable_char() {
+    use shell_quote::{Char, Reason};
+
+    for ch in u8::MIN..=u8::MAX {
+        let reason = Reason::of(Char::from_byte(ch));
+        assert_eq!(
+            reason == Reason::DialectSpecific,
+            Char::from_byte(ch).is_inert(),
+            "{ch:#04x}: {reason}"
+        );
+    }
+    assert_eq!(Reason::of(Char::from_char('é')), Reason::NonAscii);
+    let why = Bash::explain("café");
+    assert_eq!(why.len(), 1);
+    assert_eq!(
+        why[0].to_string(),
+        "`é` (U+00E9) at 3: character outside of ASCII"
+    );
+}
@@ -0,0 +1,277 @@
+#![cfg(all(
+    unix,
+    feature = "sh",
+    feature = "bash",
+    feature = "fish",
+    feature = "std"
+))]
+
+mod resources;
+mod util;
+
+use shell_quote::{Bash, Fish, Quotable, Renderer, Segment, Sh, ShellWord};
+
+/// Samples to plan and render: every byte, every pair of bytes, and text.
+fn samples() -> impl Iterator<Item = Quotable<'static>> {
+    let pairs: Vec<u8> = (0..=u8::MAX)
+        .flat_map(|a| (0..=u8::MAX).flat_map(move |b| [a, b]))
+        .collect();
+    let pairs: &'static [u8] = pairs.leak();
+    let all: &'static [u8] = (0..=u8::MAX).collect::<Vec<_>>().leak();
+    pairs
+        .chunks(2)
+        .chain(all.chunks(1))
+        .chain([all, b"", b"foo bar", b"it's", b"a\\b'c d"])
+        .map(Quotable::Bytes)
+        .chain(
+            [
+                resources::UTF8_SAMPLE,
+                "",
+                "café",
+                "caf\u{e9} \u{1F600}\t'x'",
+            ]
+            .into_iter()
+            .map(Quotable::Text),
+        )
+}
+
+// -- Planning ----------------------------------------------------------------
+
+mod plan {
+    use super::*;
+
+    #[test]
+    fn test_render_plan_is_same_as_quoting() {
+        for sample in samples() {
+            assert_eq!(
+                Sh::plan(sample).render::<Sh>(),
+                Sh::quote_vec(sample),
+                "{sample:?}"
+            );
+            assert_eq!(
+                Bash::plan(sample).render::<Bash>(),
+                Bash::quote_vec(sample),
+                "{sample:?}"
+            );
+            assert_eq!(
+                Fish::plan(sample).render::<Fish>(),
+                Fish::quote_vec(sample),
+                "{sample:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_plan_empty() {
+        let expected = [Segment::SingleQuoted(Quotable::Text(""))];
+        assert_eq!(Sh::plan("").segments(), expected);
+        assert_eq!(Bash::plan("").segments(), expected);
+        assert_eq!(Fish::plan("").segments(), expected);
+    }
+
+    #[test]
+    fn test_plan_inert() {
+        let expected = [Segment::Bare(Quotable::Text("foo"))];
+        assert_eq!(Sh::plan("foo").segments(), expected);
+        assert_eq!(Bash::plan("foo").segments(), expected);
+        assert_eq!(Fish::plan("foo").segments(), expected);
+    }
+
+    #[test]
+    fn test_plan_bash() {
+        assert_eq!(
+            Bash::plan(&b"foo\tb\xFFr"[..]).segments(),
+            [Segment::AnsiC(Quotable::Bytes(b"foo\tb\xFFr"))]
+        );
+    }
+
+    #[test]
+    fn test_plan_fish() {
+        assert_eq!(
+            Fish::plan(&b"a\tb c\\d\xFF"[..]).segments(),
+            [
+                Segment::Bare(Quotable::Bytes(b"a")),
+                Segment::Escape(b'\t'),
+                Segment::Bare(Quotable::Bytes(b"b")),
+                Segment::SingleQuoted(Quotable::Bytes(b" c\\d")),
+                Segment::Escape(0xFF),
+            ]
+        );
+    }
+}
+
+// -- Rendering ---------------------------------------------------------------
+
+mod render {
+    use super::*;
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    use super::util::{find_bins, invoke_shell};
+
+    /// Contents that are awkward in every kind of segment.
+    const AWKWARD: &[u8] = b"it's \"$HOME\" `pwd` \\ \\\\ \n \t * ~ ! \x1B \xFF";
+
+    fn words() -> Vec<ShellWord<'static>> {
+        let segments = [
+            Segment::Bare(Quotable::Bytes(b"foo")),
+            Segment::SingleQuoted(Quotable::Bytes(AWKWARD)),
+            Segment::DoubleQuoted(Quotable::Bytes(AWKWARD)),
+            Segment::AnsiC(Quotable::Bytes(AWKWARD)),
+            Segment::AnsiC(Quotable::Bytes(b"")),
+            Segment::AnsiC(Quotable::Bytes(b"foo")),
+            Segment::SingleQuoted(Quotable::Text("café")),
+            Segment::DoubleQuoted(Quotable::Text("café")),
+            Segment::AnsiC(Quotable::Text("café")),
+        ];
+        let escapes = (1..=u8::MAX).map(Segment::Escape);
+        segments
+            .into_iter()
+            .chain(escapes)
+            .map(|segment| ShellWord::new(vec![segment, segment]))
+            .chain([ShellWord::new(segments.to_vec())])
+            .collect()
+    }
+
+    /// What the shell should see once it has removed the quotes.
+    fn unquoted(word: &ShellWord) -> Vec<u8> {
+        word.segments()
+            .iter()
+            .flat_map(|segment| match segment {
+                Segment::Bare(s)
+                | Segment::SingleQuoted(s)
+                | Segment::DoubleQuoted(s)
+                | Segment::AnsiC(s) => s.as_bytes().to_vec(),
+                Segment::Escape(byte) => vec![*byte],
+            })
+            .collect()
+    }
+
+    fn check<R: Renderer>(shell: &str) {
+        for bin in find_bins(shell) {
+            for word in words() {
+                let mut script = b"printf %s ".to_vec();
+                word.render_into::<R>(&mut script);
+                let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                assert!(output.status.success(), "{word:?}");
+                assert_eq!(output.stdout, unquoted(&word), "{word:?} in {bin:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_sh() {
+        check::<Sh>("sh");
+        check::<Sh>("dash");
+    }
+
+    #[test]
+    fn test_render_bash() {
+        check::<Bash>("bash");
+    }
+
+    #[test]
+    fn test_render_fish() {
+        check::<Fish>("fish");
+    }
+
+    /// Bash cannot escape `!` in double quotes, so it is written outside.
+    #[test]
+    fn test_render_bash_bang_outside_double_quotes() {
+        let render = |s| ShellWord::new(vec![Segment::DoubleQuoted(Quotable::Text(s))]);
+        assert_eq!(render("").render::<Bash>(), b"\"\"");
+        assert_eq!(render("!").render::<Bash>(), b"\\!");
+        assert_eq!(render("a!b").render::<Bash>(), b"\"a\"\\!\"b\"");
+        assert_eq!(render("!!$x").render::<Bash>(), b"\\!\\!\"\\$x\"");
+        assert_eq!(render("a!b").render::<Sh>(), b"\"a!b\"");
+    }
+}
+
+// -- Shortest ----------------------------------------------------------------
+
+mod shortest {
+    use super::*;
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    use super::util::{find_bins, invoke_shell};
+
+    /// Awkward characters, in all combinations of up to three.
+    fn awkward() -> Vec<Vec<u8>> {
+        let chars = b"a' \"\\$`!\t\n*~=%\x1B\x7F\xE9";
+        let mut samples: Vec<Vec<u8>> = (1..=u8::MAX).map(|ch| vec![ch]).collect();
+        for &a in chars {
+            for &b in chars {
+                samples.push(vec![a, b]);
+                for &c in chars {
+                    samples.push(vec![a, b, c]);
+                }
+            }
+        }
+        samples.push(b"Bob's Files/it's \"here\"".to_vec());
+        samples
+    }
+
+    #[test]
+    fn test_shortest_is_never_longer() {
+        for sample in samples() {
+            assert!(Sh::quote_vec_shortest(sample).len() <= Sh::quote_vec(sample).len());
+            assert!(Bash::quote_vec_shortest(sample).len() <= Bash::quote_vec(sample).len());
+            assert!(Fish::quote_vec_shortest(sample).len() <= Fish::quote_vec(sample).len());
+        }
+    }
+
+    #[test]
+    fn test_shortest() {
+        assert_eq!(Sh::quote_vec_shortest(""), b"''");
+        assert_eq!(Sh::quote_vec_shortest("foo"), b"foo");
+        assert_eq!(Sh::quote_vec_shortest("it's"), b"it\\'s");
+        assert_eq!(Sh::quote_vec_shortest("it's a dog's"), b"\"it's a dog's\"");
+        assert_eq!(Sh::quote_vec_shortest("$HOME"), b"\\$HOME");
+        assert_eq!(
+            Sh::quote_vec_shortest("$HOME and $PATH"),
+            b"'$HOME and $PATH'"
+        );
+        // Never `!` in double quotes.
+        assert_eq!(
+            Bash::quote_vec_shortest("it's a dog's!"),
+            b"\"it's a dog's\"\\!"
+        );
+        assert_eq!(Bash::quote_vec_shortest(&b"\x1B[0m"[..]), b"'\x1B[0m'");
+        assert_eq!(Fish::quote_vec_shortest("a b c"), b"'a b c'");
+    }
+
+    fn check(shell: &str, quote: fn(&[u8]) -> Vec<u8>) {
+        let samples = awkward();
+        let mut script = b"printf '%s\\0'".to_vec();
+        for sample in &samples {
+            script.push(b' ');
+            script.extend(quote(sample));
+        }
+        let script = OsString::from_vec(script);
+        for bin in find_bins(shell) {
+            let output = invoke_shell(&bin, &script).unwrap();
+            let observed: Vec<&[u8]> = output.stdout.split(|&ch| ch == 0).collect();
+            assert_eq!(observed.len(), samples.len() + 1, "{bin:?}");
+            for (observed, sample) in observed.iter().zip(&samples) {
+                assert_eq!(observed, sample, "{bin:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_shortest_roundtrip_sh() {
+        check("sh", |s| Sh::quote_vec_shortest(s));
+        check("dash", |s| Sh::quote_vec_shortest(s));
+    }
+
+    #[test]
+    fn test_shortest_roundtrip_bash() {
+        check("bash", |s| Bash::quote_vec_shortest(s));
+    }
+
+    #[test]
+    fn test_shortest_roundtrip_fish() {
+        check("fish", |s| Fish::quote_vec_shortest(s));
+    }
+}
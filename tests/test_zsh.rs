@@ -0,0 +1,352 @@
+#![cfg(all(feature = "bash", feature = "std"))]
+
+mod resources;
+mod util;
+
+// -- impl Zsh ----------------------------------------------------------------
+
+mod zsh_impl {
+    use super::resources;
+    use shell_quote::{Bash, QuoteRefExt, Style, Zsh};
+
+    #[test]
+    fn test_inert() {
+        assert_eq!(Zsh::quote_vec(""), b"''");
+        assert_eq!(Zsh::quote_vec("foo/bar-1.2"), b"foo/bar-1.2");
+        assert_eq!(Zsh::quote_vec("user@example.com"), b"user@example.com");
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(Zsh::quote_vec("foo bar"), b"$'foo bar'");
+        assert_eq!(Zsh::quote_vec(r#"woo"wah""#), br#"$'woo"wah"'"#);
+        assert_eq!(Zsh::quote_vec("it's\\"), b"$'it\\'s\\\\'");
+        assert_eq!(Zsh::quote_vec("Hello \r\n"), b"$'Hello \\r\\n'");
+        assert_eq!(Zsh::quote_vec("\x07\x1B\x7F"), b"$'\\a\\e\\x7F'");
+    }
+
+    #[test]
+    fn test_nul() {
+        assert_eq!(Zsh::quote_vec("\0"), b"$'\\x00'");
+        // Not `\0`, which would run on into the digits after it.
+        assert_eq!(Zsh::quote_vec("\x001"), b"$'\\x001'");
+        assert_eq!(Zsh::unquote(&Zsh::quote_vec("\x001")).unwrap(), b"\x001");
+    }
+
+    /// `%`, `=`, `~`, `^`, and `#` may each be expanded by Z Shell, depending
+    /// on the options set, so they are always quoted.
+    #[test]
+    fn test_option_dependent_characters() {
+        for sample in ["%1", "=ls", "foo=~", "a~b", "^foo", "foo#"] {
+            assert!(Zsh::needs_quoting(sample), "{sample:?}");
+            let mut expected = b"$'".to_vec();
+            expected.extend_from_slice(sample.as_bytes());
+            expected.push(b'\'');
+            assert_eq!(Zsh::quote_vec(sample), expected, "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn test_utf8() {
+        // UTF-8 for code points U+0080 and above is included verbatim.
+        assert_eq!(Zsh::quote_vec("Hello 👋"), b"$'Hello \xf0\x9f\x91\x8b'");
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(Zsh::join_vec(["foo", "foo bar", ""]), b"foo $'foo bar' ''");
+        assert_eq!(Zsh::join_vec(Vec::<&str>::new()), b"");
+    }
+
+    #[test]
+    fn test_quotes_as_bash() {
+        // Z Shell's rules for `NUL`, `=`, `~`, and `%` are its own, but they
+        // come to the same as Bash's, so the output is the same.
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [&b"foo bar"[..], b"it's", &bytes] {
+            assert_eq!(Zsh::quote_vec(sample), Bash::quote_vec(sample));
+            // Through its own `Dialect`, as used by the traits and styles.
+            let quoted: Vec<u8> = sample.quoted(Zsh);
+            assert_eq!(quoted, Bash::quote_vec(sample));
+            let style = Style {
+                whole_word: false,
+                ..Zsh::STYLE
+            };
+            assert_eq!(
+                Zsh::with_style(style).quote_vec(sample),
+                Bash::with_style(style).quote_vec(sample)
+            );
+        }
+        assert_eq!(
+            Zsh::quote_vec(resources::UTF8_SAMPLE),
+            Bash::quote_vec(resources::UTF8_SAMPLE)
+        );
+    }
+
+    #[test]
+    fn test_needs_quoting_and_explain() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [
+            &b""[..],
+            b"foobar",
+            b"foo bar",
+            b"FOO=bar",
+            &bytes,
+            resources::UTF8_SAMPLE.as_bytes(),
+        ] {
+            let quoted = Zsh::quote_vec(sample);
+            assert_eq!(Zsh::needs_quoting(sample), quoted != sample, "{sample:?}");
+            assert_eq!(
+                Zsh::explain(sample).is_empty(),
+                quoted == sample || sample.is_empty(),
+                "{sample:?}"
+            );
+            assert_eq!(Zsh::quoted_len(sample), quoted.len());
+            assert_eq!(Zsh::plan(sample).render::<Zsh>(), quoted);
+            assert_eq!(Zsh::display(sample).to_string().as_bytes(), quoted);
+        }
+    }
+
+    #[test]
+    fn test_unquote_roundtrip() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        for sample in [&b"foo bar"[..], b"it's", &bytes] {
+            assert_eq!(Zsh::unquote(&Zsh::quote_vec(sample)).unwrap(), sample);
+            assert_eq!(
+                Zsh::unquote(&Zsh::quote_vec_shortest(sample)).unwrap(),
+                sample
+            );
+        }
+        let args = ["print", "-r", "--", "foo bar", "", "\0"];
+        let split = Zsh::split(&Zsh::join_vec(args)).unwrap();
+        assert_eq!(split, args.map(str::as_bytes));
+    }
+}
+
+// -- Round trips -------------------------------------------------------------
+
+#[cfg(unix)]
+mod zsh_roundtrip {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    use super::{
+        resources,
+        util::{find_bins, invoke_shell},
+    };
+    use shell_quote::Zsh;
+
+    /// Run `print -rn --` with `sample`, quoted, in every `zsh` on `PATH`,
+    /// after `setup`. `print` is a builtin, so `sample` can contain NUL.
+    fn check(setup: &str, sample: &[u8], quote: fn(&[u8]) -> Vec<u8>) {
+        for bin in find_bins("zsh") {
+            let mut script = format!("{setup}\nprint -rn -- ").into_bytes();
+            script.extend(quote(sample));
+            let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+            assert_eq!(output.stdout, sample, "{bin:?}");
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_bytes_including_nul() {
+        let bytes: Vec<u8> = (0..=u8::MAX).collect();
+        check("", &bytes, |s| Zsh::quote_vec(s));
+        check("", &bytes, |s| Zsh::quote_vec_shortest(s));
+        check("", b"\x001\x00", |s| Zsh::quote_vec(s));
+    }
+
+    #[test]
+    fn test_roundtrip_utf8() {
+        check("", resources::UTF8_SAMPLE.as_bytes(), |s| Zsh::quote_vec(s));
+    }
+
+    /// None of these options change how a quoted word is read.
+    #[test]
+    fn test_roundtrip_with_options() {
+        let setup = "setopt equals magic_equal_subst extended_glob ksh_glob";
+        for sample in ["=ls", "foo=~", "--dir=~/bin", "^foo", "a~b", "x#", "@(a)"] {
+            check(setup, sample.as_bytes(), |s| Zsh::quote_vec(s));
+            check(setup, sample.as_bytes(), |s| Zsh::quote_vec_shortest(s));
+        }
+    }
+
+    /// Z Shell tests the literal token for a job specification, so a quoted
+    /// `%1` in command position is looked up as a command, and not found.
+    #[test]
+    fn test_job_spec_is_not_a_job_spec_when_quoted() {
+        for bin in find_bins("zsh") {
+            for name in ["%1", "%foo", "%", "%%", "%+", "%-", "%?x"] {
+                let mut script = Zsh::quote_vec(name);
+                script.extend(b" 2>/dev/null; print -rn -- $?");
+                let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                assert_eq!(output.stdout, b"127", "{bin:?} {name:?}");
+            }
+        }
+    }
+}
+
+// -- Zsh::quote_flag ---------------------------------------------------------
+
+mod zsh_flags {
+    use super::resources;
+    use shell_quote::{Zsh, ZshFlag};
+
+    const FLAGS: [ZshFlag; 6] = [
+        ZshFlag::Q,
+        ZshFlag::Qq,
+        ZshFlag::Qqq,
+        ZshFlag::Qqqq,
+        ZshFlag::QMinus,
+        ZshFlag::QPlus,
+    ];
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut samples: Vec<Vec<u8>> = Vec::new();
+        // Each byte at the start of a word, and after another character.
+        for ch in 0..=u8::MAX {
+            samples.push(vec![ch]);
+            samples.push(vec![b'x', ch]);
+        }
+        samples.push((0..=u8::MAX).collect());
+        samples.push(resources::UTF8_SAMPLE.into());
+        for sample in [
+            "",
+            "foobar",
+            "foo bar",
+            "'",
+            "''",
+            "it's",
+            "it's here",
+            "'quoted'",
+            "~",
+            "~/bin",
+            "a~b",
+            "=ls",
+            "a=b",
+            "#a",
+            "a#",
+            "%1",
+            "a!b",
+            "$'x'",
+            "\x0001",
+        ] {
+            samples.push(sample.into());
+        }
+        samples
+    }
+
+    fn quote(flag: ZshFlag, s: &str) -> String {
+        String::from_utf8(Zsh::quote_flag(flag, s)).unwrap()
+    }
+
+    #[test]
+    fn test_flag() {
+        let flags = FLAGS.map(ZshFlag::flag);
+        assert_eq!(flags, ["q", "qq", "qqq", "qqqq", "q-", "q+"]);
+    }
+
+    #[test]
+    fn test_q() {
+        let q = |s| quote(ZshFlag::Q, s);
+        assert_eq!(q(""), "");
+        assert_eq!(q("foo/bar"), "foo/bar");
+        assert_eq!(q("it's here"), r"it\'s\ here");
+        assert_eq!(q("=ls ~/a=~b"), r"\=ls\ ~/a=~b");
+        assert_eq!(q("a!b%c"), "a!b%c");
+        assert_eq!(q("a\tb\nc"), r"a$'\t'b$'\n'c");
+        assert_eq!(q("\0\x1B\x7F"), r"$'\0'$'\033'$'\177'");
+        assert_eq!(q("\x001"), r"$'\000'1");
+        assert_eq!(q("é"), r"$'\303'$'\251'");
+    }
+
+    #[test]
+    fn test_qq_qqq_qqqq() {
+        assert_eq!(quote(ZshFlag::Qq, ""), "''");
+        assert_eq!(quote(ZshFlag::Qq, "it's\n"), "'it'\\''s\n'");
+        assert_eq!(quote(ZshFlag::Qqq, ""), r#""""#);
+        assert_eq!(
+            quote(ZshFlag::Qqq, r#"$x`y`"z"\!"#),
+            r#""\$x\`y\`\"z\"\\!""#
+        );
+        assert_eq!(quote(ZshFlag::Qqq, "$'x'"), r#""$'x'""#);
+        assert_eq!(quote(ZshFlag::Qqqq, ""), "$''");
+        assert_eq!(quote(ZshFlag::Qqqq, "it's\\!"), r"$'it\'s\\\!'");
+        assert_eq!(quote(ZshFlag::Qqqq, "\0\x1Bé"), r"$'\0\033\303\251'");
+    }
+
+    #[test]
+    fn test_q_minus() {
+        let q = |s| quote(ZshFlag::QMinus, s);
+        assert_eq!(q(""), "''");
+        assert_eq!(q("foo/bar"), "foo/bar");
+        assert_eq!(q("foo bar"), "'foo bar'");
+        assert_eq!(q("'"), r"\'");
+        assert_eq!(q("it's"), r"it\'s");
+        assert_eq!(q("it's here"), r"it\''s here'");
+        assert_eq!(q("a b'c d"), r"'a b'\''c d'");
+        assert_eq!(q("a=b"), "a=b");
+    }
+
+    #[test]
+    fn test_q_plus() {
+        let q = |s| quote(ZshFlag::QPlus, s);
+        assert_eq!(q(""), "''");
+        assert_eq!(q("foo/bar"), "foo/bar");
+        assert_eq!(q("a=b"), "'a=b'");
+        assert_eq!(q("it's here"), r"'it'\''s here'");
+        assert_eq!(q("'quoted'"), r"\''quoted'\'");
+        assert_eq!(q("it's\t\\"), r"$'it\'s\t\\'");
+        assert_eq!(q("\0\x1B\x7F"), r"$'\C-@\C-[\C-?'");
+        let bytes = Zsh::quote_flag(ZshFlag::QPlus, b"\xC3\xA9\x80");
+        assert_eq!(bytes, br"$'\M-C\M-)\M-\C-@'");
+    }
+
+    /// Z Shell's output is the same, in the `C` locale.
+    #[cfg(unix)]
+    #[test]
+    fn test_agrees_with_zsh() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        for bin in find_bins("zsh") {
+            for sample in samples() {
+                let mut script = b"export LC_ALL=C\nx=".to_vec();
+                Zsh::quote_into_vec(&sample, &mut script);
+                let mut expected = Vec::new();
+                for flag in FLAGS {
+                    script.extend(format!("\nprint -r -- ${{({})x}}", flag.flag()).bytes());
+                    expected.extend(Zsh::quote_flag(flag, &sample));
+                    expected.push(b'\n');
+                }
+                let output = invoke_shell(&bin, &OsString::from_vec(script)).unwrap();
+                assert_eq!(output.stdout, expected, "{bin:?} {sample:?}");
+            }
+        }
+    }
+
+    /// `(qq)`, `(qqq)`, and `(q-)` write `NUL` as it is, so a script with one
+    /// cannot be passed to `zsh -c`, which takes a C string.
+    #[cfg(unix)]
+    #[test]
+    fn test_roundtrip() {
+        use super::util::{find_bins, invoke_shell};
+        use std::{ffi::OsString, io, os::unix::ffi::OsStringExt};
+        for bin in find_bins("zsh") {
+            for flag in FLAGS {
+                for sample in samples() {
+                    let mut script = b"export LC_ALL=C\nprint -rn -- ".to_vec();
+                    script.extend(Zsh::quote_flag(flag, &sample));
+                    let output = invoke_shell(&bin, &OsString::from_vec(script));
+                    if sample.contains(&0)
+                        && matches!(flag, ZshFlag::Qq | ZshFlag::Qqq | ZshFlag::QMinus)
+                    {
+                        let error = output.unwrap_err();
+                        assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{flag:?}");
+                    } else {
+                        let output = output.unwrap();
+                        assert_eq!(output.stdout, sample, "{bin:?} {flag:?} {sample:?}");
+                    }
+                }
+            }
+        }
+    }
+}